@@ -1,20 +1,55 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
 use dashmap::DashMap;
+use futures::future::join_all;
 use octocrab::models::issues::Issue;
-use octocrab::models::{Author, Repository};
+use octocrab::models::repos::RepoCommit;
+use octocrab::models::teams::RequestedTeam;
+use octocrab::models::{Author, Collaborator, IssueState, Label, Repository, UserProfile};
 use octocrab::params::State;
 use octocrab::Octocrab;
+use percent_encoding::percent_decode_str;
 use ropey::Rope;
+use serde_json::{json, Value};
+use tokio::time::timeout;
 use tower_lsp::jsonrpc::{self, Result};
 use tower_lsp::lsp_types::{
-    CompletionItem, CompletionTextEdit, Hover, HoverContents, MarkupContent, MarkupKind,
-    MessageType, Range, TextDocumentItem, TextEdit,
+    ClientCapabilities, CodeAction, CodeActionKind, CodeActionOrCommand, Command, CompletionItem,
+    CompletionItemKind, CompletionTextEdit, Diagnostic, DiagnosticSeverity,
+    DidChangeWatchedFilesRegistrationOptions, DocumentChangeOperation, DocumentChanges,
+    Documentation, FileSystemWatcher, GlobPattern, Hover, HoverContents, Location, MarkupContent,
+    MarkupKind, MessageType, OneOf, OptionalVersionedTextDocumentIdentifier, Range, Registration,
+    RenameFile, ResourceOp, ResourceOperationKind, SymbolInformation, SymbolKind, TextDocumentEdit,
+    TextDocumentItem, TextEdit, Url, WorkspaceEdit,
 };
 use tower_lsp::{lsp_types::Position, Client};
 
+use crate::changelog::{self, Change};
+use crate::codeowners;
+use crate::commit;
+use crate::gh::action::{self, Action, Uses};
+use crate::gh::emoji::Emoji;
+use crate::gh::issue::IssueActivity;
 use crate::gh::wiki::WikiArticle;
 use crate::gh::{self, GetDetail, GetEdit, GetLabel};
+use crate::git;
+use crate::markdown::{self, Heading};
+use crate::pull_request;
+use crate::reference::{self, Reference, Target};
+use crate::template::{self, Field};
+use crate::todo;
+use crate::workflow;
 
-pub const TRIGGER_CHARACTERS: [char; 5] = ['[', '#', ':', '@', '/'];
+pub const TRIGGER_CHARACTERS: [char; 6] = ['[', '#', ':', '@', '/', '('];
+pub const COPY_PERMALINK: &str = "github-lsp.copyPermalink";
+pub const CREATE_ISSUE: &str = "github-lsp.createIssue";
+pub const GENERATE_CHANGELOG: &str = "github-lsp.generateChangelog";
+pub const COMMANDS: [&str; 3] = [COPY_PERMALINK, CREATE_ISSUE, GENERATE_CHANGELOG];
 
 #[derive(Debug)]
 pub struct Backend {
@@ -23,32 +58,95 @@ pub struct Backend {
     pub(crate) repository_map: DashMap<String, Repository>,
     pub(crate) issue_map: DashMap<String, Issue>,
     pub(crate) member_map: DashMap<String, Author>,
+    /// Only the members of the organization, `member_map` has the collaborators too
+    pub(crate) org_member_map: DashMap<String, Author>,
+    pub(crate) profile_map: DashMap<String, UserProfile>,
+    pub(crate) org_map: DashMap<String, Vec<String>>,
+    pub(crate) commit_map: DashMap<String, Option<RepoCommit>>,
+    pub(crate) issue_activity_map: DashMap<String, IssueActivity>,
+    pub(crate) team_map: DashMap<String, RequestedTeam>,
+    pub(crate) label_map: DashMap<String, Label>,
+    pub(crate) contributor_map: DashMap<String, Vec<String>>,
     pub(crate) wiki_map: DashMap<String, WikiArticle>,
+    pub(crate) emoji_map: DashMap<String, Emoji>,
+    pub(crate) markdown_map: DashMap<String, Vec<Heading>>,
+    pub(crate) reference_map: DashMap<String, Vec<Reference>>,
+    pub(crate) language_map: DashMap<String, String>,
+    pub(crate) action_map: DashMap<String, Action>,
+    pub(crate) action_tag_map: DashMap<String, Vec<String>>,
+    pub(crate) tree_map: DashMap<String, Vec<String>>,
     octocrab: Octocrab,
     owner: String,
     repo: String,
+    root: PathBuf,
+    wiki_dir: OnceLock<PathBuf>,
+    pub(crate) client_capabilities: OnceLock<ClientCapabilities>,
+    completion_generation: Arc<AtomicUsize>,
+    issue_search_map: Arc<DashMap<String, Vec<Issue>>>,
+    repo_search_map: Arc<DashMap<String, Vec<Repository>>>,
 }
 
 impl Backend {
     const PER_PAGE: u8 = 100;
+    const MIN_REMOTE_NEEDLE: usize = 2;
+    const DEBOUNCE: Duration = Duration::from_millis(300);
+    const REMOTE_TIMEOUT: Duration = Duration::from_millis(2000);
+    const MAX_PROFILE_LOOKUPS: usize = 20;
+    const MAX_CONTRIBUTOR_LOOKUPS: usize = 10;
+    const MAX_SEARCHES: usize = 50;
 
-    pub fn new(client: Client, octocrab: Octocrab, owner: String, repo: String) -> Backend {
+    pub fn new(
+        client: Client,
+        octocrab: Octocrab,
+        owner: String,
+        repo: String,
+        root: PathBuf,
+    ) -> Backend {
         Backend {
             client,
             octocrab,
             owner,
             repo,
+            root,
+            wiki_dir: OnceLock::new(),
+            client_capabilities: OnceLock::new(),
+            completion_generation: Arc::new(AtomicUsize::new(0)),
+            issue_search_map: Arc::new(DashMap::new()),
+            repo_search_map: Arc::new(DashMap::new()),
             document_map: DashMap::new(),
             repository_map: DashMap::new(),
             issue_map: DashMap::new(),
             member_map: DashMap::new(),
+            org_member_map: DashMap::new(),
+            profile_map: DashMap::new(),
+            org_map: DashMap::new(),
+            commit_map: DashMap::new(),
+            issue_activity_map: DashMap::new(),
+            team_map: DashMap::new(),
+            label_map: DashMap::new(),
+            contributor_map: DashMap::new(),
             wiki_map: DashMap::new(),
+            emoji_map: DashMap::new(),
+            markdown_map: DashMap::new(),
+            reference_map: DashMap::new(),
+            language_map: DashMap::new(),
+            action_map: DashMap::new(),
+            action_tag_map: DashMap::new(),
+            tree_map: DashMap::new(),
         }
     }
 
     pub(crate) async fn initialize(&self) {
+        // local files first, documents opened while we wait for GitHub already link to them
+        self.initialize_markdown().await;
+        self.initialize_references().await;
+        // a single request, and without it every `:` would fall back to searching owners
+        self.initialize_emojis().await;
         self.initialize_issues().await;
         self.initialize_members().await;
+        self.initialize_collaborators().await;
+        self.initialize_teams().await;
+        self.initialize_labels().await;
         self.initialize_repos_as("owner").await;
         self.initialize_repos_as("organization_member").await;
         self.initialize_wiki().await;
@@ -59,22 +157,30 @@ impl Backend {
         //FIX: probably will cause issues for someone, maybe?
         if link.contains("github.com") {
             let link = link.replace("https://github.com/", "");
-            let parts = link.split('/');
+            let mut parts = link.split('/');
             let identifier = parts
-                .last()
+                .next_back()
                 .ok_or("No issue part in URL")
                 .map_err(|_| jsonrpc::Error::method_not_found())?;
-            if link.contains("issues") {
-                let issue = self
-                    .issue_map
-                    .iter()
-                    .filter(|issue| issue.get_label().starts_with(&format!("#{} ", identifier)))
-                    .last()
-                    .ok_or("No issue")
+            let segments = link.split(['/', '#']).collect::<Vec<&str>>();
+            if let [owner, repo, "issues" | "pull", number, ..] = segments[..] {
+                let number = number
+                    .parse()
                     .map_err(|_| jsonrpc::Error::method_not_found())?;
-                text = issue.get_detail().to_string();
+                text = self
+                    .on_issue_hover(owner, repo, number)
+                    .await
+                    .ok_or(jsonrpc::Error::method_not_found())?;
+            } else if let [owner, repo, "commit", sha, ..] = segments[..] {
+                text = self
+                    .on_commit_hover(owner, repo, sha)
+                    .await
+                    .ok_or(jsonrpc::Error::method_not_found())?;
             } else if link.contains("wiki") {
-                text = format!("# Wiki article {}", identifier);
+                text = self
+                    .on_wiki_hover(&link)
+                    .await
+                    .unwrap_or_else(|| format!("# Wiki article {}", identifier));
             } else if link.contains('/') {
                 let repository = self
                     .repository_map
@@ -85,22 +191,16 @@ impl Backend {
                     .map_err(|_| jsonrpc::Error::method_not_found())?;
                 text = repository.get_detail().to_string();
             } else {
-                let users = octocrab::instance()
-                    .search()
-                    .users(identifier)
-                    .per_page(1)
-                    .page(0u32)
-                    .send()
+                text = self
+                    .on_user_hover(identifier)
                     .await
-                    .map_err(|_| {
-                        tower_lsp::jsonrpc::Error::new(
-                            tower_lsp::jsonrpc::ErrorCode::MethodNotFound,
-                        )
-                    })?;
-                let user = &users.items[0];
-                text = format!("# User {}", user.login.to_owned());
+                    .ok_or(jsonrpc::Error::method_not_found())?;
             }
         }
+        // not a link to GitHub, leave it to other servers
+        if text.is_empty() {
+            return Ok(None);
+        }
         let hover = Hover {
             contents: HoverContents::Markup(MarkupContent {
                 kind: MarkupKind::Markdown,
@@ -111,6 +211,616 @@ impl Backend {
         Ok(Some(hover))
     }
 
+    /// Hover for `#123`, `GH-123`, `owner/repo#123`, `@user`, SHAs and known `owner/repo` names
+    /// outside of links, with the same details as hovering their links
+    pub async fn on_reference_hover(&self, uri: &Url, position: Position) -> Option<Hover> {
+        let target = self
+            .reference_map
+            .get(&uri.to_string())?
+            .iter()
+            .find(|reference| reference.contains(position))
+            .map(|reference| reference.target.to_owned());
+        let text = match target {
+            Some(Target::Issue {
+                owner,
+                repo,
+                number,
+            }) => self.on_issue_hover(&owner, &repo, number).await?,
+            Some(Target::User { login }) => self.on_user_hover(&login).await?,
+            Some(Target::Commit { owner, repo, sha }) => {
+                self.on_commit_hover(&owner, &repo, &sha).await?
+            }
+            None => self.repository_at(uri, position)?,
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: text,
+            }),
+            range: None,
+        })
+    }
+
+    /// Details of a repository we know whose `owner/repo` name is under the cursor
+    fn repository_at(&self, uri: &Url, position: Position) -> Option<String> {
+        let line = self
+            .document_map
+            .get(&uri.to_string())?
+            .get_line(position.line as usize)?
+            .to_string();
+        let is_name = |ch: &char| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.' | '/');
+        let chars = line.chars().collect::<Vec<char>>();
+        let character = (position.character as usize).min(chars.len());
+        let start = character
+            - chars[..character]
+                .iter()
+                .rev()
+                .take_while(|ch| is_name(ch))
+                .count();
+        let end = character
+            + chars[character..]
+                .iter()
+                .take_while(|ch| is_name(ch))
+                .count();
+        let name = chars[start..end]
+            .iter()
+            .collect::<String>()
+            .trim_matches('.')
+            .to_owned();
+        if !name.contains('/') {
+            return None;
+        }
+        self.repository_map
+            .iter()
+            .find(|repo| repo.get_label() == name)
+            .map(|repo| repo.get_detail())
+    }
+
+    /// An issue or PR of any repository, ours are already in the `issue_map`. Reactions, linked
+    /// PRs and recent comments are fetched on the first hover and kept in the `issue_activity_map`
+    async fn on_issue_hover(&self, owner: &str, repo: &str, number: u64) -> Option<String> {
+        let issue = (owner == self.owner && repo == self.repo)
+            .then(|| self.find_issue(number))
+            .flatten();
+        let issue = match issue {
+            Some(issue) => issue,
+            None => self.octocrab.issues(owner, repo).get(number).await.ok()?,
+        };
+        let key = format!("{owner}/{repo}#{number}");
+        let cached = self
+            .issue_activity_map
+            .get(&key)
+            .map(|activity| activity.value().clone());
+        let activity = match cached {
+            Some(activity) => Some(activity),
+            None => {
+                let activity = gh::issue::find_issue_activity(&self.octocrab, owner, repo, &issue)
+                    .await
+                    .ok();
+                if let Some(activity) = &activity {
+                    self.issue_activity_map.insert(key, activity.clone());
+                }
+                activity
+            }
+        };
+        let mut detail = issue.get_detail();
+        if let Some(activity) = activity {
+            detail.push_str(&activity.get_detail());
+        }
+        Some(detail)
+    }
+
+    /// A commit of any repository, fetched once and kept in the `commit_map`, also when it turns
+    /// out not to be a commit at all
+    async fn on_commit_hover(&self, owner: &str, repo: &str, sha: &str) -> Option<String> {
+        let key = format!("{owner}/{repo}@{sha}");
+        if let Some(commit) = self.commit_map.get(&key) {
+            return commit.as_ref().map(|commit| commit.get_detail());
+        }
+        let commit = self.octocrab.commits(owner, repo).get(sha).await.ok();
+        let detail = commit.as_ref().map(|commit| commit.get_detail());
+        self.commit_map.insert(key, commit);
+        detail
+    }
+
+    /// The profile of a user and the organizations they are in, both looked up once per login
+    async fn on_user_hover(&self, login: &str) -> Option<String> {
+        let profile = self.find_profile(login).await?;
+        let mut orgs = self.find_orgs(login).await;
+        // private members of our own organization are still members
+        if self.org_member_map.contains_key(login) && !orgs.contains(&self.owner) {
+            orgs.insert(0, self.owner.to_owned());
+        }
+        let mut detail = profile.get_detail();
+        if !orgs.is_empty() {
+            let orgs = orgs
+                .iter()
+                .map(|org| format!("[{org}](https://github.com/{org})"))
+                .collect::<Vec<String>>();
+            detail.push_str(&format!("\n\nOrganizations: {}", orgs.join(", ")));
+        }
+        Some(detail)
+    }
+
+    /// The public organizations of a user, fetched once and kept in the `org_map`
+    async fn find_orgs(&self, login: &str) -> Vec<String> {
+        if let Some(orgs) = self.org_map.get(login) {
+            return orgs.value().clone();
+        }
+        let Ok(orgs) = gh::user::find_orgs(&self.octocrab, login).await else {
+            return vec![];
+        };
+        self.org_map.insert(login.to_owned(), orgs.clone());
+        orgs
+    }
+
+    /// Details of a wiki article, the last edit is looked up once and kept in the `wiki_map`
+    async fn on_wiki_hover(&self, link: &str) -> Option<String> {
+        let link = link.split('#').next().unwrap_or_default();
+        let uri = format!("/{}", link.trim_end_matches('/'));
+        let key = self
+            .wiki_map
+            .iter()
+            .find(|article| article.uri == uri)
+            .map(|article| article.key().to_owned())?;
+        let path = self
+            .wiki_map
+            .get(&key)
+            .filter(|article| article.last_edited.is_none())
+            .map(|article| article.path.to_owned());
+        if let Some(path) = path {
+            let last_edited = gh::wiki::find_last_edited(&path).await.ok();
+            if let Some(mut article) = self.wiki_map.get_mut(&key) {
+                article.last_edited = last_edited;
+            }
+        }
+        self.wiki_map.get(&key).map(|article| article.get_detail())
+    }
+
+    /// Resolve a relative link to a file and heading, or a wiki link to a local wiki checkout
+    pub async fn on_definition(&self, uri: &Url, link: String) -> Option<Location> {
+        let (path, line) = if markdown::is_relative(&link) {
+            let (file, anchor) = link.split_once('#').unwrap_or((link.as_str(), ""));
+            let path = self.resolve_target(uri, file);
+            let line = self.markdown_map.get(&path).and_then(|headings| {
+                headings
+                    .iter()
+                    .find(|heading| heading.slug == anchor)
+                    .map(|heading| heading.line)
+            });
+            (self.root.join(path), line)
+        } else {
+            let (link, anchor) = link.split_once('#').unwrap_or((link.as_str(), ""));
+            let article = self
+                .wiki_map
+                .iter()
+                .find(|article| link.ends_with(&format!("github.com{}", article.uri)))?;
+            let line = article
+                .headings
+                .iter()
+                .find(|heading| heading.slug == anchor)
+                .map(|heading| heading.line);
+            (article.path.to_owned(), line)
+        };
+        if !path.exists() {
+            return None;
+        }
+        let position = Position {
+            line: line.unwrap_or_default(),
+            character: 0,
+        };
+        Some(Location {
+            uri: Url::from_file_path(path).ok()?,
+            range: Range {
+                start: position,
+                end: position,
+            },
+        })
+    }
+
+    /// Every place in the workspace that refers to the same thing as the cursor does
+    pub async fn on_references(&self, uri: &Url, position: Position) -> Option<Vec<Location>> {
+        let target = self
+            .reference_map
+            .get(&uri.to_string())?
+            .iter()
+            .find(|reference| reference.contains(position))?
+            .target
+            .to_owned();
+        let locations = self
+            .reference_map
+            .iter()
+            .flat_map(|entry| {
+                let uri = Url::parse(entry.key()).ok();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|reference| reference.target == target)
+                    .filter_map(|reference| {
+                        Some(Location {
+                            uri: uri.clone()?,
+                            range: reference.range,
+                        })
+                    })
+                    .collect::<Vec<Location>>()
+            })
+            .collect::<Vec<Location>>();
+        Some(locations)
+    }
+
+    /// Issues, PRs, repositories and wiki pages matching the query, for symbol pickers
+    pub async fn on_workspace_symbol(&self, query: &str) -> Vec<SymbolInformation> {
+        let query = query.to_lowercase();
+        #[allow(deprecated)]
+        let symbol = |name: String, kind: SymbolKind, container: String, uri: Option<Url>| {
+            Some(SymbolInformation {
+                name,
+                kind,
+                tags: None,
+                deprecated: None,
+                location: Location {
+                    uri: uri?,
+                    range: Range::default(),
+                },
+                container_name: Some(container),
+            })
+        };
+        // issues and repositories have no file, their location is the page on GitHub
+        let issues = self.issue_map.iter().filter_map(|issue| {
+            let kind = if issue.pull_request.is_some() {
+                "pulls"
+            } else {
+                "issues"
+            };
+            symbol(
+                issue.get_label(),
+                SymbolKind::EVENT,
+                format!("{}/{}/{kind}", self.owner, self.repo),
+                Some(issue.html_url.to_owned()),
+            )
+        });
+        let repositories = self.repository_map.iter().filter_map(|repo| {
+            symbol(
+                repo.get_label(),
+                SymbolKind::PACKAGE,
+                "repositories".into(),
+                repo.html_url.to_owned(),
+            )
+        });
+        // wiki pages open locally from the checkout
+        let wiki = self.wiki_map.iter().filter_map(|article| {
+            symbol(
+                article.title.to_owned(),
+                SymbolKind::FILE,
+                format!("{}/{}/wiki", self.owner, self.repo),
+                Url::from_file_path(&article.path).ok(),
+            )
+        });
+        issues
+            .chain(repositories)
+            .chain(wiki)
+            .filter(|symbol| symbol.name.to_lowercase().contains(&query)) //TODO: smarter fuzzy match
+            .collect()
+    }
+
+    /// Rename a wiki page, updating every link to it in the workspace and the wiki checkout
+    pub async fn on_rename_wiki(
+        &self,
+        page: &str,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>> {
+        let Some(key) = self.find_wiki_article(page) else {
+            return Ok(None);
+        };
+        let Some((old_uri, old_path)) = self
+            .wiki_map
+            .get(&key)
+            .map(|article| (article.uri.to_owned(), article.path.to_owned()))
+        else {
+            return Ok(None);
+        };
+        let new_title = new_name.trim().replace('-', " ");
+        let new_page = new_title.replace(' ', "-");
+        let new_uri = format!("/{}/{}/wiki/{new_page}", self.owner, self.repo);
+        let wiki_dir = self.wiki_dir.get();
+
+        let mut files = git::ls_files(&self.root)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| self.root.join(file))
+            .collect::<Vec<PathBuf>>();
+        if let Some(wiki_dir) = wiki_dir {
+            let wiki_files = git::ls_files(wiki_dir).await.unwrap_or_default();
+            files.extend(wiki_files.into_iter().map(|file| wiki_dir.join(file)));
+        }
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for path in files {
+            if !markdown::is_markdown(&path.to_string_lossy()) {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let Some(text) = self.read_document(&uri).await else {
+                continue;
+            };
+            let mut edits = markdown::links(&text)
+                .into_iter()
+                .filter_map(|link| {
+                    let (target, anchor) = link
+                        .target
+                        .split_once('#')
+                        .map(|(target, anchor)| (target, format!("#{anchor}")))
+                        .unwrap_or((link.target.as_str(), String::new()));
+                    target
+                        .ends_with(&format!("github.com{old_uri}"))
+                        .then(|| TextEdit {
+                            range: link.range,
+                            new_text: format!("https://github.com{new_uri}{anchor}"),
+                        })
+                })
+                .collect::<Vec<TextEdit>>();
+            if wiki_dir.is_some_and(|wiki_dir| path.starts_with(wiki_dir)) {
+                edits.extend(
+                    markdown::wiki_links(&text)
+                        .into_iter()
+                        .filter(|link| self.find_wiki_article(&link.target).as_ref() == Some(&key))
+                        .map(|link| TextEdit {
+                            range: link.range,
+                            new_text: new_title.to_owned(),
+                        }),
+                );
+            }
+            if !edits.is_empty() {
+                changes.insert(uri, edits);
+            }
+        }
+
+        // renaming the page file is optional, clients that can not do it still get the links
+        let can_rename_file = self
+            .client_capabilities
+            .get()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|workspace| workspace.workspace_edit.as_ref())
+            .and_then(|workspace_edit| workspace_edit.resource_operations.as_ref())
+            .is_some_and(|operations| operations.contains(&ResourceOperationKind::Rename));
+        if !can_rename_file {
+            return Ok(Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..WorkspaceEdit::default()
+            }));
+        }
+        let new_path = old_path.with_file_name(format!(
+            "{new_page}.{}",
+            old_path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+                .unwrap_or("md".into())
+        ));
+        let (Ok(old_file), Ok(new_file)) = (
+            Url::from_file_path(&old_path),
+            Url::from_file_path(&new_path),
+        ) else {
+            return Ok(None);
+        };
+        let mut operations = changes
+            .into_iter()
+            .map(|(uri, edits)| {
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect::<Vec<DocumentChangeOperation>>();
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+            RenameFile {
+                old_uri: old_file,
+                new_uri: new_file,
+                options: None,
+                annotation_id: None,
+            },
+        )));
+        // the `wiki_map` follows once the client actually renamed the file, see `reindex_wiki`
+        Ok(Some(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    /// The page of a link into our own wiki, `Home` for the wiki root
+    pub fn wiki_page(&self, link: &str) -> Option<String> {
+        let wiki = format!("github.com/{}/{}/wiki", self.owner, self.repo);
+        let (_, page) = link.split_once(&wiki)?;
+        let page = page.split('#').next().unwrap_or_default().trim_matches('/');
+        Some(if page.is_empty() { "Home" } else { page }.to_owned())
+    }
+
+    /// The open version of a document, or its contents on disk
+    async fn read_document(&self, uri: &Url) -> Option<String> {
+        if let Some(rope) = self.document_map.get(&uri.to_string()) {
+            return Some(rope.to_string());
+        }
+        tokio::fs::read_to_string(uri.to_file_path().ok()?)
+            .await
+            .ok()
+    }
+
+    /// Fill in the documentation of the highlighted completion item
+    pub async fn on_completion_resolve(&self, mut item: CompletionItem) -> CompletionItem {
+        let issue = item
+            .data
+            .as_ref()
+            .and_then(|data| data["issue"].as_u64())
+            .and_then(|number| self.find_issue(number));
+        if let Some(issue) = issue {
+            item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: issue.get_detail(),
+            }));
+        }
+        item
+    }
+
+    /// Run a remote search after a pause in typing, unless a newer keystroke cancelled it. The
+    /// hits are kept per needle in a bounded cache and merged into the completions for the next
+    /// keystroke, the shared maps stay as they are
+    fn spawn_search<T, F>(&self, searches: &Arc<DashMap<String, Vec<T>>>, needle: &str, search: F)
+    where
+        T: Send + Sync + 'static,
+        F: Future<Output = octocrab::Result<Vec<T>>> + Send + 'static,
+    {
+        // every keystroke cancels a pending search, also when it needs no search of its own
+        let generation = self.completion_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        if needle.len() < Backend::MIN_REMOTE_NEEDLE || searches.contains_key(needle) {
+            return;
+        }
+        let completion_generation = self.completion_generation.clone();
+        let searches = searches.clone();
+        let client = self.client.clone();
+        let needle = needle.to_owned();
+        tokio::spawn(async move {
+            tokio::time::sleep(Backend::DEBOUNCE).await;
+            if completion_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            match timeout(Backend::REMOTE_TIMEOUT, search).await {
+                Ok(Ok(items)) => {
+                    // make room by dropping any earlier search
+                    if searches.len() >= Backend::MAX_SEARCHES {
+                        let evicted = searches.iter().next().map(|entry| entry.key().to_owned());
+                        if let Some(evicted) = evicted {
+                            searches.remove(&evicted);
+                        }
+                    }
+                    searches.insert(needle, items);
+                }
+                _ => {
+                    client
+                        .log_message(MessageType::WARNING, "Remote search failed")
+                        .await
+                }
+            }
+        });
+    }
+
+    /// Whatever earlier searches for the needle, or the start of it, found remotely
+    fn searched<T: Clone>(searches: &DashMap<String, Vec<T>>, needle: &str) -> Vec<T> {
+        searches
+            .iter()
+            .filter(|entry| needle.starts_with(entry.key().as_str()))
+            .flat_map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Cached issues right away, merged with what the search API found in the repo for the needle
+    pub(crate) async fn search_issue_and_pr_live(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        let octocrab = self.octocrab.clone();
+        let query = format!("repo:{}/{} {}", self.owner, self.repo, needle);
+        self.spawn_search(&self.issue_search_map, needle, async move {
+            octocrab
+                .search()
+                .issues_and_pull_requests(&query)
+                .per_page(Backend::PER_PAGE)
+                .send()
+                .await
+                .map(|page| page.items)
+        });
+        let mut completion_items = self.search_issue_and_pr(position, needle).await?;
+        let lowercase = needle.to_lowercase();
+        let mut numbers = self
+            .issue_map
+            .iter()
+            .map(|issue| issue.number)
+            .collect::<Vec<u64>>();
+        for issue in Backend::searched(&self.issue_search_map, needle) {
+            if numbers.contains(&issue.number)
+                || !issue.get_label().to_lowercase().contains(&lowercase)
+            {
+                continue;
+            }
+            numbers.push(issue.number);
+            completion_items.push(Backend::issue_item(position, needle, &issue));
+        }
+        Ok(completion_items)
+    }
+
+    /// Cached repos right away, merged with what the search API found all over GitHub
+    pub(crate) async fn search_repo_live(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        let octocrab = self.octocrab.clone();
+        let query = needle.to_owned();
+        self.spawn_search(&self.repo_search_map, needle, async move {
+            octocrab
+                .search()
+                .repositories(&query)
+                .per_page(Backend::PER_PAGE)
+                .send()
+                .await
+                .map(|page| page.items)
+        });
+        let mut completion_items = self.search_repo(position, needle).await?;
+        let lowercase = needle.to_lowercase();
+        let mut labels = self
+            .repository_map
+            .iter()
+            .map(|repo| repo.get_label())
+            .collect::<Vec<String>>();
+        for repo in Backend::searched(&self.repo_search_map, needle) {
+            let label = repo.get_label();
+            if labels.contains(&label) || !repo.get_detail().to_lowercase().contains(&lowercase) {
+                continue;
+            }
+            labels.push(label);
+            completion_items.push(Backend::repo_item(position, needle, &repo));
+        }
+        Ok(completion_items)
+    }
+
+    fn issue_item(position: Position, needle: &str, issue: &Issue) -> CompletionItem {
+        CompletionItem {
+            label: issue.get_label(),
+            // the documentation is filled in by on_completion_resolve
+            data: Some(json!({ "issue": issue.number })),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range {
+                    start: Position {
+                        line: position.line,
+                        character: position.character - needle.len() as u32 - 1,
+                    },
+                    end: position,
+                },
+                new_text: issue.get_edit(),
+            })),
+            ..CompletionItem::default()
+        }
+    }
+
+    fn repo_item(position: Position, needle: &str, repo: &Repository) -> CompletionItem {
+        CompletionItem {
+            label: repo.get_label(),
+            detail: Some(repo.get_detail()),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range {
+                    start: Position {
+                        line: position.line,
+                        character: position.character - needle.len() as u32 - 1,
+                    },
+                    end: position,
+                },
+                new_text: repo.get_edit(),
+            })),
+            ..CompletionItem::default()
+        }
+    }
+
     pub(crate) async fn search_issue_and_pr(
         &self,
         position: Position,
@@ -122,14 +832,44 @@ impl Backend {
                 format!("search_issue_and_pr: {}", needle),
             )
             .await;
-        //TODO: refresh issues
+        let lowercase = needle.to_lowercase();
+        let completion_items = self
+            .issue_map
+            .iter()
+            .filter(|issue| {
+                issue.get_label().to_lowercase().contains(&lowercase)
+                    || issue
+                        .body
+                        .as_ref()
+                        .is_some_and(|body| body.to_lowercase().contains(&lowercase))
+            }) //TODO: smarter fuzzy match
+            .map(|issue| Backend::issue_item(position, needle, &issue))
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// Issues as `#123` for plain text commit messages, `open_only` after a closing keyword
+    pub(crate) async fn search_commit_issue(
+        &self,
+        position: Position,
+        needle: &str,
+        open_only: bool,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("search_commit_issue: {}", needle),
+            )
+            .await;
+        let lowercase = needle.to_lowercase();
         let completion_items = self
             .issue_map
             .iter()
-            .filter(|issue| issue.get_detail().contains(needle)) //TODO: smarter fuzzy match
+            .filter(|issue| !open_only || matches!(issue.state, IssueState::Open))
+            .filter(|issue| issue.get_label().to_lowercase().contains(&lowercase)) //TODO: smarter fuzzy match
             .map(|issue| CompletionItem {
                 label: issue.get_label(),
-                detail: Some(issue.get_detail()),
+                data: Some(json!({ "issue": issue.number })),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                     range: Range {
                         start: Position {
@@ -138,7 +878,7 @@ impl Backend {
                         },
                         end: position,
                     },
-                    new_text: issue.get_edit(),
+                    new_text: format!("#{}", issue.number),
                 })),
                 ..CompletionItem::default()
             })
@@ -146,21 +886,94 @@ impl Backend {
         Ok(completion_items)
     }
 
-    pub(crate) async fn search_user(
+    /// `Co-authored-by: ` trailers for members, replacing the start of the line typed so far
+    pub(crate) async fn search_co_author(
+        &self,
+        position: Position,
+        line: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_co_author: {}", line))
+            .await;
+        let needle = line
+            .get(commit::CO_AUTHORED_BY.len()..)
+            .unwrap_or_default()
+            .to_lowercase();
+        let members = self
+            .member_map
+            .iter()
+            .filter(|member| member.login.to_lowercase().contains(&needle)) //TODO: smarter fuzzy match
+            .map(|member| member.value().clone())
+            .collect::<Vec<Author>>();
+        // names need the full profile, look up what we can before the completion goes stale
+        let profiles = members
+            .iter()
+            .take(Backend::MAX_PROFILE_LOOKUPS)
+            .map(|member| self.find_profile(&member.login));
+        let _ = timeout(Backend::REMOTE_TIMEOUT, join_all(profiles)).await;
+        let completion_items = members
+            .iter()
+            .map(|member| {
+                let name = self
+                    .profile_map
+                    .get(&member.login)
+                    .and_then(|profile| profile.name.to_owned());
+                let trailer = gh::author::co_authored_by(member, name.as_deref());
+                CompletionItem {
+                    label: trailer.to_owned(),
+                    filter_text: Some(line.to_owned()),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range: Range {
+                            start: Position {
+                                line: position.line,
+                                character: 0,
+                            },
+                            end: position,
+                        },
+                        new_text: trailer,
+                    })),
+                    ..CompletionItem::default()
+                }
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// The full profile of a user, fetched once and kept in the `profile_map`
+    pub(crate) async fn find_profile(&self, login: &str) -> Option<UserProfile> {
+        if let Some(profile) = self.profile_map.get(login) {
+            return Some(profile.value().clone());
+        }
+        let profile = self.octocrab.users(login).profile().await.ok()?;
+        self.profile_map.insert(login.to_owned(), profile.clone());
+        Some(profile)
+    }
+
+    /// `@login` and `@org/team` owners for CODEOWNERS
+    pub(crate) async fn search_code_owner(
         &self,
         position: Position,
         needle: &str,
     ) -> Result<Vec<CompletionItem>> {
         self.client
-            .log_message(MessageType::INFO, format!("search_user: {}", needle))
+            .log_message(MessageType::INFO, format!("search_code_owner: {}", needle))
             .await;
-        let completion_items = self
+        let members = self
             .member_map
             .iter()
-            .filter(|member| member.get_detail().contains(needle)) //TODO: smarter fuzzy match
-            .map(|member| CompletionItem {
-                label: member.get_label(),
-                detail: Some(member.get_detail()),
+            .map(|member| format!("@{}", member.login))
+            .collect::<Vec<String>>();
+        let teams = self
+            .team_map
+            .iter()
+            .map(|team| format!("@{}/{}", self.owner, team.slug))
+            .collect::<Vec<String>>();
+        let completion_items = members
+            .into_iter()
+            .chain(teams)
+            .filter(|owner| owner.contains(needle)) //TODO: smarter fuzzy match
+            .map(|owner| CompletionItem {
+                label: owner.to_owned(),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                     range: Range {
                         start: Position {
@@ -169,7 +982,7 @@ impl Backend {
                         },
                         end: position,
                     },
-                    new_text: member.get_edit(),
+                    new_text: owner,
                 })),
                 ..CompletionItem::default()
             })
@@ -177,30 +990,49 @@ impl Backend {
         Ok(completion_items)
     }
 
-    pub(crate) async fn search_wiki(
+    /// Root anchored files and directories of the working tree for CODEOWNERS patterns
+    pub(crate) async fn search_code_owner_path(
         &self,
         position: Position,
         needle: &str,
     ) -> Result<Vec<CompletionItem>> {
         self.client
-            .log_message(MessageType::INFO, format!("search_wiki: {}", needle))
+            .log_message(
+                MessageType::INFO,
+                format!("search_code_owner_path: {}", needle),
+            )
             .await;
-        let completion_items = self
-            .wiki_map
+        let files = git::ls_files(&self.root)
+            .await
+            .map_err(|_| jsonrpc::Error::internal_error())?;
+        let mut patterns = files
             .iter()
-            .filter(|member| member.title.contains(needle)) //TODO: smarter fuzzy match
-            .map(|member| CompletionItem {
-                label: member.title.to_owned(),
-                detail: None,
+            .flat_map(|file| {
+                let mut patterns = file
+                    .match_indices('/')
+                    .map(|(end, _)| (format!("/{}/", &file[..end]), CompletionItemKind::FOLDER))
+                    .collect::<Vec<(String, CompletionItemKind)>>();
+                patterns.push((format!("/{file}"), CompletionItemKind::FILE));
+                patterns
+            })
+            .filter(|(pattern, _)| pattern.contains(needle)) //TODO: smarter fuzzy match
+            .collect::<Vec<(String, CompletionItemKind)>>();
+        patterns.sort_by(|a, b| a.0.cmp(&b.0));
+        patterns.dedup_by(|a, b| a.0 == b.0);
+        let completion_items = patterns
+            .into_iter()
+            .map(|(pattern, kind)| CompletionItem {
+                label: pattern.to_owned(),
+                kind: Some(kind),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                     range: Range {
                         start: Position {
                             line: position.line,
-                            character: position.character - needle.len() as u32 - 1,
+                            character: position.character - needle.len() as u32,
                         },
                         end: position,
                     },
-                    new_text: member.get_edit(),
+                    new_text: pattern,
                 })),
                 ..CompletionItem::default()
             })
@@ -208,31 +1040,56 @@ impl Backend {
         Ok(completion_items)
     }
 
-    pub(crate) async fn search_repo(
+    /// Actions for a workflow `uses:`, and the tags of the action once there is an `@`
+    pub(crate) async fn search_action(
         &self,
         position: Position,
         needle: &str,
     ) -> Result<Vec<CompletionItem>> {
         self.client
-            .log_message(MessageType::INFO, format!("search_repo: {}", needle))
+            .log_message(MessageType::INFO, format!("search_action: {}", needle))
             .await;
-        //TODO: should we enable searching for repos _all_ over github? Maybe?
-        let completion_items = self
-            .repository_map
+        let range = |typed: &str| Range {
+            start: Position {
+                line: position.line,
+                character: position.character - typed.chars().count() as u32,
+            },
+            end: position,
+        };
+        if let Some((uses, reference)) = needle.split_once('@') {
+            let Some(Uses::Remote { owner, repo, .. }) = Uses::parse(uses) else {
+                return Ok(vec![]);
+            };
+            let tags = self.find_action_tags(&owner, &repo).await;
+            let completion_items = tags
+                .into_iter()
+                .filter(|tag| tag.starts_with(reference))
+                .map(|tag| CompletionItem {
+                    label: tag.to_owned(),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range: range(reference),
+                        new_text: tag,
+                    })),
+                    ..CompletionItem::default()
+                })
+                .collect::<Vec<CompletionItem>>();
+            return Ok(completion_items);
+        }
+        let mut actions = action::POPULAR_ACTIONS
             .iter()
-            .filter(|repo| repo.get_detail().contains(needle)) //TODO: smarter fuzzy match
-            .map(|repo| CompletionItem {
-                label: repo.get_label(),
-                detail: Some(repo.get_detail()),
+            .map(|action| action.to_string())
+            .chain(self.repository_map.iter().map(|repo| repo.get_label()))
+            .filter(|action| action.contains(needle)) //TODO: smarter fuzzy match
+            .collect::<Vec<String>>();
+        actions.sort();
+        actions.dedup();
+        let completion_items = actions
+            .into_iter()
+            .map(|action| CompletionItem {
+                label: action.to_owned(),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                    range: Range {
-                        start: Position {
-                            line: position.line,
-                            character: position.character - needle.len() as u32 - 1,
-                        },
-                        end: position,
-                    },
-                    new_text: repo.get_edit(),
+                    range: range(needle),
+                    new_text: action,
                 })),
                 ..CompletionItem::default()
             })
@@ -240,43 +1097,103 @@ impl Backend {
         Ok(completion_items)
     }
 
-    pub(crate) async fn search_owner(
+    /// The tags of an action repository, fetched once and kept in the `action_tag_map`
+    async fn find_action_tags(&self, owner: &str, repo: &str) -> Vec<String> {
+        let key = format!("{owner}/{repo}");
+        if let Some(tags) = self.action_tag_map.get(&key) {
+            return tags.value().clone();
+        }
+        let Ok(tags) = action::find_action_tags(&self.octocrab, owner, repo).await else {
+            return vec![];
+        };
+        self.action_tag_map.insert(key, tags.clone());
+        tags
+    }
+
+    /// The name, description and inputs of the action a workflow step `uses:`, remote actions are
+    /// fetched once per version and kept in the `action_map`
+    pub async fn on_action_hover(&self, uses: &str) -> Option<Hover> {
+        let action = match Uses::parse(uses)? {
+            Uses::Remote {
+                owner,
+                repo,
+                path,
+                reference,
+            } => {
+                if let Some(action) = self.action_map.get(uses) {
+                    Some(action.value().clone())
+                } else {
+                    let action =
+                        action::find_action(&self.octocrab, uses, &owner, &repo, &path, &reference)
+                            .await?;
+                    self.action_map.insert(uses.to_owned(), action.clone());
+                    Some(action)
+                }
+            }
+            // local actions change along with the workflow, so read them every time
+            Uses::Local(path) => {
+                let dir = self.root.join(path);
+                let mut yaml = None;
+                for file in ["action.yml", "action.yaml"] {
+                    if let Ok(text) = tokio::fs::read_to_string(dir.join(file)).await {
+                        yaml = Some(text);
+                        break;
+                    }
+                }
+                action::parse_action(uses, &yaml?)
+            }
+            Uses::Docker => None,
+        }?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: action.get_detail(),
+            }),
+            range: None,
+        })
+    }
+
+    /// Labels and assignees of an issue template, as plain names without `@`
+    pub(crate) async fn search_template_value(
         &self,
         position: Position,
+        field: Field,
         needle: &str,
     ) -> Result<Vec<CompletionItem>> {
         self.client
-            .log_message(MessageType::INFO, format!("search_owner: {}", needle))
+            .log_message(
+                MessageType::INFO,
+                format!("search_template_value: {:?} {}", field, needle),
+            )
             .await;
-        let needle = needle.replace(':', "");
-        if needle.is_empty() {
-            return Ok(vec![]);
-        }
-        let users = octocrab::instance()
-            .search()
-            .users(&needle)
-            // .sort("followers")
-            // .order("desc")
-            .send()
-            .await
-            .map_err(|_| {
-                tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::MethodNotFound)
-            })?;
-        let completion_items = users
+        let values = match field {
+            Field::Labels => self
+                .label_map
+                .iter()
+                .map(|label| (label.get_label(), label.get_detail()))
+                .collect::<Vec<(String, String)>>(),
+            Field::Assignees => self
+                .member_map
+                .iter()
+                .map(|member| (member.get_label(), member.get_detail()))
+                .collect::<Vec<(String, String)>>(),
+        };
+        let lowercase = needle.to_lowercase();
+        let completion_items = values
             .into_iter()
-            .filter(|member| member.login.starts_with(&needle))
-            .map(|member| CompletionItem {
-                label: member.get_label(),
-                detail: Some(member.get_detail()),
+            .filter(|(value, _)| value.to_lowercase().contains(&lowercase)) //TODO: smarter fuzzy match
+            .map(|(value, detail)| CompletionItem {
+                label: value.to_owned(),
+                detail: Some(detail),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                     range: Range {
                         start: Position {
                             line: position.line,
-                            character: position.character - needle.len() as u32 - 1,
+                            character: position.character - needle.chars().count() as u32,
                         },
                         end: position,
                     },
-                    new_text: member.get_edit(),
+                    new_text: value,
                 })),
                 ..CompletionItem::default()
             })
@@ -284,10 +1201,905 @@ impl Backend {
         Ok(completion_items)
     }
 
-    pub(crate) async fn on_change(&self, params: TextDocumentItem) {
-        let rope = ropey::Rope::from_str(&params.text);
-        self.document_map
+    /// `@login` mentions for a pull request, the recent contributors to the files the branch
+    /// changed first and then every other member
+    pub(crate) async fn search_reviewer(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_reviewer: {}", needle))
+            .await;
+        let mut contributors: Vec<(String, usize)> = vec![];
+        for login in self.find_branch_contributors().await {
+            match contributors.iter_mut().find(|(known, _)| *known == login) {
+                Some((_, count)) => *count += 1,
+                None => contributors.push((login, 1)),
+            }
+        }
+        contributors.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let members = self
+            .member_map
+            .iter()
+            .map(|member| member.login.to_owned())
+            .filter(|login| !contributors.iter().any(|(known, _)| known == login))
+            .map(|login| (login, 0))
+            .collect::<Vec<(String, usize)>>();
+        let completion_items = contributors
+            .into_iter()
+            .chain(members)
+            .filter(|(login, _)| login.contains(needle)) //TODO: smarter fuzzy match
+            .enumerate()
+            .map(|(index, (login, commits))| CompletionItem {
+                label: format!("@{login}"),
+                detail: (commits > 0)
+                    .then(|| format!("{commits} recent commits to the changed files")),
+                // keep the most active contributors on top
+                sort_text: Some(format!("{index:04}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32 - 1,
+                        },
+                        end: position,
+                    },
+                    new_text: format!("@{login}"),
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// The authors of recent commits to the files the current branch changed, once per commit,
+    /// each file is looked up once and kept in the `contributor_map`
+    async fn find_branch_contributors(&self) -> Vec<String> {
+        let Ok(base) = git::default_branch(&self.root).await else {
+            return vec![];
+        };
+        let files = git::changed_files(&self.root, &base)
+            .await
+            .unwrap_or_default();
+        let mut logins: Vec<String> = vec![];
+        for file in files.iter().take(Backend::MAX_CONTRIBUTOR_LOOKUPS) {
+            if let Some(contributors) = self.contributor_map.get(file) {
+                logins.extend(contributors.iter().cloned());
+                continue;
+            }
+            let Ok(authors) =
+                gh::author::find_contributors(&self.octocrab, &self.owner, &self.repo, file).await
+            else {
+                continue;
+            };
+            let contributors = authors
+                .into_iter()
+                .map(|author| author.login)
+                .collect::<Vec<String>>();
+            logins.extend(contributors.iter().cloned());
+            self.contributor_map.insert(file.to_owned(), contributors);
+        }
+        logins
+    }
+
+    pub(crate) async fn search_user(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_user: {}", needle))
+            .await;
+        let completion_items = self
+            .member_map
+            .iter()
+            .filter(|member| member.get_detail().contains(needle)) //TODO: smarter fuzzy match
+            .map(|member| CompletionItem {
+                label: member.get_label(),
+                detail: Some(member.get_detail()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32 - 1,
+                        },
+                        end: position,
+                    },
+                    new_text: member.get_edit(),
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    pub(crate) async fn search_wiki(
+        &self,
+        uri: &Url,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_wiki: {}", needle))
+            .await;
+        // inside the wiki we link with [[Page Name]] or [[Text|Page Name]] instead of URLs
+        let wiki = self.is_wiki_document(uri);
+        let typed = needle.trim_start_matches('[');
+        let (text, page) = match typed.split_once('|') {
+            Some((text, page)) if wiki => (Some(text), page),
+            _ => (None, typed),
+        };
+        let completion_items = self
+            .wiki_map
+            .iter()
+            .filter(|member| member.title.contains(page)) //TODO: smarter fuzzy match
+            .map(|member| CompletionItem {
+                label: member.title.to_owned(),
+                detail: None,
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32 - 1,
+                        },
+                        end: position,
+                    },
+                    new_text: match text {
+                        _ if !wiki => member.get_edit(),
+                        Some(text) => format!("[[{text}|{}]]", member.title),
+                        None => format!("[[{}]]", member.title),
+                    },
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    pub(crate) async fn search_repo(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_repo: {}", needle))
+            .await;
+        let lowercase = needle.to_lowercase();
+        let completion_items = self
+            .repository_map
+            .iter()
+            .filter(|repo| repo.get_detail().to_lowercase().contains(&lowercase)) //TODO: smarter fuzzy match
+            .map(|repo| Backend::repo_item(position, needle, &repo))
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    pub(crate) async fn search_emoji(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_emoji: {}", needle))
+            .await;
+        // an empty needle would list every emoji
+        if needle.is_empty() {
+            return Ok(vec![]);
+        }
+        let completion_items = self
+            .emoji_map
+            .iter()
+            .filter(|emoji| emoji.shortcode.contains(needle)) //TODO: smarter fuzzy match
+            .map(|emoji| CompletionItem {
+                label: emoji.get_label(),
+                detail: Some(emoji.url.to_owned()),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: emoji.get_detail(),
+                })),
+                // rank exact prefixes first, e.g. :rocket before :space_rocket
+                sort_text: Some(format!(
+                    "{}{}",
+                    u8::from(!emoji.shortcode.starts_with(needle)),
+                    emoji.shortcode
+                )),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32 - 1,
+                        },
+                        end: position,
+                    },
+                    new_text: emoji.get_edit(),
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    pub(crate) async fn search_owner(
+        &self,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_owner: {}", needle))
+            .await;
+        let start = position.character - needle.len() as u32 - 1;
+        let needle = needle.replace(':', "");
+        if needle.is_empty() {
+            return Ok(vec![]);
+        }
+        let users = octocrab::instance()
+            .search()
+            .users(&needle)
+            // .sort("followers")
+            // .order("desc")
+            .send()
+            .await
+            .map_err(|_| {
+                tower_lsp::jsonrpc::Error::new(tower_lsp::jsonrpc::ErrorCode::MethodNotFound)
+            })?;
+        let completion_items = users
+            .into_iter()
+            .filter(|member| member.login.starts_with(&needle))
+            .map(|member| CompletionItem {
+                label: member.get_label(),
+                detail: Some(member.get_detail()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: start,
+                        },
+                        end: position,
+                    },
+                    new_text: member.get_edit(),
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// Complete the target of a `[text](` link with files relative to the current document
+    pub(crate) async fn search_path(
+        &self,
+        uri: &Url,
+        position: Position,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_path: {}", needle))
+            .await;
+        if needle.contains("://") {
+            return Ok(vec![]);
+        }
+        if let Some((file, anchor)) = needle.split_once('#') {
+            return self.search_anchor(uri, position, file, anchor).await;
+        }
+        let files = git::ls_files(&self.root)
+            .await
+            .map_err(|_| jsonrpc::Error::internal_error())?;
+        let base = self.document_dir(uri);
+        let completion_items = files
+            .iter()
+            .map(|file| git::relative_path(&base, Path::new(file)))
+            .filter(|file| file.contains(needle)) //TODO: smarter fuzzy match
+            .map(|file| CompletionItem {
+                label: file.to_owned(),
+                kind: Some(CompletionItemKind::FILE),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32,
+                        },
+                        end: position,
+                    },
+                    new_text: file,
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// Complete the path of a `https://github.com/<owner>/<repo>/blob/<ref>/<path>` link,
+    /// `repo_link` is everything before `/blob/` and `blob` everything after it
+    pub(crate) async fn search_blob_path(
+        &self,
+        position: Position,
+        repo_link: &str,
+        blob: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(MessageType::INFO, format!("search_blob_path: {}", blob))
+            .await;
+        let Some((reference, needle)) = blob.split_once('/') else {
+            return Ok(vec![]);
+        };
+        let mut repo_parts = repo_link.rsplit('/');
+        let (Some(repo), Some(owner)) = (repo_parts.next(), repo_parts.next()) else {
+            return Ok(vec![]);
+        };
+        let local = if owner == self.owner && repo == self.repo {
+            git::ls_tree(&self.root, reference).await.ok()
+        } else {
+            None
+        };
+        let files = match local {
+            Some(files) => files,
+            None => self
+                .find_tree_paths(owner, repo, reference)
+                .await
+                .ok_or(jsonrpc::Error::method_not_found())?,
+        };
+        let completion_items = files
+            .into_iter()
+            .filter(|file| file.contains(needle)) //TODO: smarter fuzzy match
+            .map(|file| CompletionItem {
+                label: file.to_owned(),
+                kind: Some(CompletionItemKind::FILE),
+                detail: Some(format!("{owner}/{repo}@{reference}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32,
+                        },
+                        end: position,
+                    },
+                    new_text: file,
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// The files of another repository at a ref, fetched once and kept in the `tree_map`
+    async fn find_tree_paths(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+    ) -> Option<Vec<String>> {
+        let key = format!("{owner}/{repo}@{reference}");
+        if let Some(files) = self.tree_map.get(&key) {
+            return Some(files.value().clone());
+        }
+        let files = gh::repo::find_tree_paths(&self.octocrab, owner, repo, reference)
+            .await
+            .ok()?;
+        self.tree_map.insert(key, files.clone());
+        Some(files)
+    }
+
+    /// `https://github.com/<owner>/<repo>/blob/<sha>/<path>#L<start>-L<end>` at the current HEAD
+    pub(crate) async fn permalink(&self, uri: &Url, range: Range) -> Result<String> {
+        let path = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.strip_prefix(&self.root).ok().map(git::to_slash))
+            .ok_or(jsonrpc::Error::invalid_params(
+                "File is not in the repository",
+            ))?;
+        let sha = git::head_sha(&self.root)
+            .await
+            .map_err(|_| jsonrpc::Error::internal_error())?;
+        let start = range.start.line + 1;
+        // a selection of whole lines ends at the start of the next line
+        let end = if range.end.character == 0 && range.end.line > range.start.line {
+            range.end.line
+        } else {
+            range.end.line + 1
+        };
+        let lines = if start == end {
+            format!("L{start}")
+        } else {
+            format!("L{start}-L{end}")
+        };
+        Ok(format!(
+            "https://github.com/{}/{}/blob/{sha}/{path}#{lines}",
+            self.owner, self.repo
+        ))
+    }
+
+    /// A changelog section for the pull requests merged and commits pushed between two tags, which
+    /// must be known to the local repo
+    pub(crate) async fn generate_changelog(&self, from: &str, to: &str) -> Result<String> {
+        let commits = git::first_parent_log(&self.root, from, to)
+            .await
+            .map_err(|_| jsonrpc::Error::invalid_params(format!("Unknown range {from}..{to}")))?;
+        let base = format!("https://github.com/{}/{}", self.owner, self.repo);
+        let mut changes: Vec<Change> = vec![];
+        for (sha, subject, body) in commits {
+            let change = match changelog::pull_request(&subject) {
+                Some(number) => {
+                    // merge commits keep the title of the pull request in their body
+                    let title = if subject.starts_with("Merge pull request #") {
+                        match body.lines().next().filter(|title| !title.is_empty()) {
+                            Some(title) => title.to_owned(),
+                            None => self
+                                .octocrab
+                                .pulls(&self.owner, &self.repo)
+                                .get(number)
+                                .await
+                                .ok()
+                                .and_then(|pull| pull.title)
+                                .unwrap_or(subject.to_owned()),
+                        }
+                    } else {
+                        changelog::strip_pull_request(&subject).to_owned()
+                    };
+                    Change {
+                        title,
+                        link: format!("([#{number}]({base}/pull/{number}))"),
+                    }
+                }
+                None => Change {
+                    title: subject,
+                    link: format!("([{}]({base}/commit/{sha}))", &sha[..7.min(sha.len())]),
+                },
+            };
+            changes.push(change);
+        }
+        let heading = if to == "HEAD" { "Unreleased" } else { to };
+        Ok(changelog::render(
+            heading,
+            &format!("{base}/compare/{from}...{to}"),
+            &changes,
+        ))
+    }
+
+    /// Complete the `#anchor` of a link to the headings of a local Markdown file
+    pub(crate) async fn search_anchor(
+        &self,
+        uri: &Url,
+        position: Position,
+        file: &str,
+        needle: &str,
+    ) -> Result<Vec<CompletionItem>> {
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("search_anchor: {}#{}", file, needle),
+            )
+            .await;
+        let Some(headings) = self.markdown_map.get(&self.resolve_target(uri, file)) else {
+            return Ok(vec![]);
+        };
+        let completion_items = headings
+            .iter()
+            .filter(|heading| heading.slug.contains(needle)) //TODO: smarter fuzzy match
+            .map(|heading| CompletionItem {
+                label: heading.slug.to_owned(),
+                kind: Some(CompletionItemKind::REFERENCE),
+                detail: Some(heading.title.to_owned()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: Range {
+                        start: Position {
+                            line: position.line,
+                            character: position.character - needle.len() as u32,
+                        },
+                        end: position,
+                    },
+                    new_text: heading.slug.to_owned(),
+                })),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+        Ok(completion_items)
+    }
+
+    /// The language the client opened the document with
+    pub(crate) fn language_of(&self, uri: &Url) -> String {
+        self.language_map
+            .get(&uri.to_string())
+            .map(|language| language.to_owned())
+            .unwrap_or("markdown".into())
+    }
+
+    /// `COMMIT_EDITMSG` and friends are plain text where `#` starts a comment
+    pub(crate) fn is_commit_message(&self, uri: &Url) -> bool {
+        self.language_of(uri) == "gitcommit" || uri.path().ends_with("COMMIT_EDITMSG")
+    }
+
+    pub(crate) fn is_codeowners(&self, uri: &Url) -> bool {
+        codeowners::is_codeowners(uri.path())
+    }
+
+    pub(crate) fn is_workflow(&self, uri: &Url) -> bool {
+        workflow::is_workflow(uri.path())
+    }
+
+    pub(crate) fn is_pull_request(&self, uri: &Url) -> bool {
+        uri.to_file_path()
+            .is_ok_and(|path| pull_request::is_pull_request(&path))
+    }
+
+    pub(crate) fn is_issue_template(&self, uri: &Url) -> bool {
+        template::is_issue_template(uri.path())
+    }
+
+    /// Documents in the wiki checkout link to each other with `[[Page Name]]`
+    fn is_wiki_document(&self, uri: &Url) -> bool {
+        let Some(wiki_dir) = self.wiki_dir.get() else {
+            return false;
+        };
+        uri.to_file_path()
+            .is_ok_and(|path| path.starts_with(wiki_dir))
+    }
+
+    /// A document relative to the repository root
+    fn document_path(&self, uri: &Url) -> Option<PathBuf> {
+        uri.to_file_path()
+            .ok()
+            .and_then(|path| path.strip_prefix(&self.root).ok().map(Path::to_path_buf))
+    }
+
+    /// Create an issue from the text in `range`, the first line is the title and the rest the body
+    pub(crate) async fn create_issue(&self, uri: &Url, range: Range) -> Result<Issue> {
+        let text = self
+            .text_in(uri, range)
+            .ok_or(jsonrpc::Error::invalid_params("No text selected"))?;
+        let (title, body) = text.split_once('\n').unwrap_or((text.as_str(), ""));
+        let (title, body) = (title.trim(), body.trim());
+        if title.is_empty() {
+            return Err(jsonrpc::Error::invalid_params("No title for the issue"));
+        }
+        let issue = self
+            .octocrab
+            .issues(&self.owner, &self.repo)
+            .create(title)
+            .body::<String>((!body.is_empty()).then(|| body.into()))
+            .send()
+            .await
+            .map_err(|_| jsonrpc::Error::internal_error())?;
+        self.issue_map.insert(issue.title.to_owned(), issue.clone());
+        Ok(issue)
+    }
+
+    /// Commands offered for the selection, or for the untracked TODO comment under the cursor
+    pub async fn on_code_action(&self, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+        let command = |title: &str, command: &str, arguments: Vec<Value>| {
+            CodeActionOrCommand::Command(Command {
+                title: title.into(),
+                command: command.into(),
+                arguments: Some([json!(uri)].into_iter().chain(arguments).collect()),
+            })
+        };
+        let mut actions = vec![command(
+            "Copy permalink",
+            COPY_PERMALINK,
+            vec![json!(range)],
+        )];
+        if range.start != range.end {
+            actions.push(command(
+                "Create issue from selection",
+                CREATE_ISSUE,
+                vec![json!(range)],
+            ));
+        } else if let Some(rope) = self.document_map.get(&uri.to_string()) {
+            todo::todos(&rope.to_string())
+                .into_iter()
+                .filter(|todo| todo.range.start.line == range.start.line && todo.issue.is_none())
+                .for_each(|todo| {
+                    // annotate the TODO with the new issue instead of replacing its text
+                    actions.push(command(
+                        "Create issue from TODO",
+                        CREATE_ISSUE,
+                        vec![json!(todo.range), json!(todo.marker_end)],
+                    ));
+                });
+        }
+        if let Some(action) = self.insert_pull_request_template(uri).await {
+            actions.push(action);
+        }
+        actions
+    }
+
+    /// Start a pull request description from the template of the repository, unless it is there
+    async fn insert_pull_request_template(&self, uri: &Url) -> Option<CodeActionOrCommand> {
+        if !self.is_pull_request(uri) {
+            return None;
+        }
+        let mut template = None;
+        for path in pull_request::TEMPLATES {
+            if let Ok(text) = tokio::fs::read_to_string(self.root.join(path)).await {
+                template = Some(text);
+                break;
+            }
+        }
+        let template = template?;
+        let text = self.document_map.get(&uri.to_string())?.to_string();
+        if text.contains(template.trim()) {
+            return None;
+        }
+        let position = Position {
+            line: 0,
+            character: 0,
+        };
+        Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Insert pull request template".into(),
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(
+                    uri.to_owned(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: position,
+                            end: position,
+                        },
+                        new_text: template,
+                    }],
+                )])),
+                ..WorkspaceEdit::default()
+            }),
+            ..CodeAction::default()
+        }))
+    }
+
+    /// The text of an open document between two positions
+    fn text_in(&self, uri: &Url, range: Range) -> Option<String> {
+        let rope = self.document_map.get(&uri.to_string())?;
+        let char_at = |position: Position| {
+            let line = rope.try_line_to_char(position.line as usize).ok()?;
+            Some((line + position.character as usize).min(rope.len_chars()))
+        };
+        let (start, end) = (char_at(range.start)?, char_at(range.end)?);
+        rope.get_slice(start..end).map(|slice| slice.to_string())
+    }
+
+    /// The directory of a document relative to the repository root
+    fn document_dir(&self, uri: &Url) -> PathBuf {
+        self.document_path(uri)
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default()
+    }
+
+    /// The repository relative path a link in the document points to, an empty path is the
+    /// document itself and a leading `/` is the repository root
+    fn resolve_target(&self, uri: &Url, target: &str) -> String {
+        // `my%20file.md` is `my file.md` on disk
+        let target = percent_decode_str(target).decode_utf8_lossy();
+        let target = target.as_ref();
+        let path = if target.is_empty() {
+            self.document_path(uri).unwrap_or_default()
+        } else if let Some(target) = target.strip_prefix('/') {
+            PathBuf::from(target)
+        } else {
+            self.document_dir(uri).join(target)
+        };
+        git::to_slash(&git::normalize_path(&path))
+    }
+
+    /// Warn about relative links to files or headings that do not exist
+    fn link_diagnostics(&self, uri: &Url, text: &str) -> Vec<Diagnostic> {
+        markdown::links(text)
+            .into_iter()
+            .filter(|link| markdown::is_relative(&link.target))
+            .filter_map(|link| {
+                let (file, anchor) = link
+                    .target
+                    .split_once('#')
+                    .unwrap_or((link.target.as_str(), ""));
+                let path = self.resolve_target(uri, file);
+                let message = if !file.is_empty() && !self.root.join(&path).exists() {
+                    format!("No file {path}")
+                } else if anchor.is_empty()
+                    || !markdown::is_markdown(&path)
+                    // files not indexed yet can not be checked
+                    || self
+                        .markdown_map
+                        .get(&path)
+                        .is_none_or(|headings| headings.iter().any(|h| h.slug == anchor))
+                {
+                    return None;
+                } else {
+                    format!("No heading #{anchor} in {path}")
+                };
+                Some(Diagnostic {
+                    range: link.range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    source: Some("github-lsp".into()),
+                    message,
+                    ..Diagnostic::default()
+                })
+            })
+            .collect::<Vec<Diagnostic>>()
+    }
+
+    /// Warn about `[[Page Name]]` links to wiki pages that do not exist
+    fn wiki_link_diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        markdown::wiki_links(text)
+            .into_iter()
+            .filter(|link| self.find_wiki_article(&link.target).is_none())
+            .map(|link| Diagnostic {
+                range: link.range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                source: Some("github-lsp".into()),
+                message: format!("No wiki page {}", link.target),
+                ..Diagnostic::default()
+            })
+            .collect::<Vec<Diagnostic>>()
+    }
+
+    /// Warn about CODEOWNERS patterns without files, and owners we do not know of
+    async fn codeowners_diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        let files = git::ls_files(&self.root).await.unwrap_or_default();
+        let warning = |range: Range, message: String| Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            source: Some("github-lsp".into()),
+            message,
+            ..Diagnostic::default()
+        };
+        let mut diagnostics: Vec<Diagnostic> = vec![];
+        for rule in codeowners::rules(text) {
+            if !files.is_empty()
+                && !files
+                    .iter()
+                    .any(|file| codeowners::matches(&rule.pattern, file))
+            {
+                diagnostics.push(warning(
+                    rule.range,
+                    format!("No files match {}", rule.pattern),
+                ));
+            }
+            // e-mail owners can not be checked, and empty maps mean we could not list them
+            for (owner, range) in rule.owners {
+                let Some(handle) = owner.strip_prefix('@') else {
+                    continue;
+                };
+                // logins and team slugs are case-insensitive
+                let unknown = match handle.split_once('/') {
+                    Some((org, team)) => {
+                        org.eq_ignore_ascii_case(&self.owner)
+                            && !self.team_map.is_empty()
+                            && !self
+                                .team_map
+                                .iter()
+                                .any(|known| known.key().eq_ignore_ascii_case(team))
+                    }
+                    None => {
+                        !self.member_map.is_empty()
+                            && !self
+                                .member_map
+                                .iter()
+                                .any(|known| known.key().eq_ignore_ascii_case(handle))
+                    }
+                };
+                if unknown {
+                    diagnostics.push(warning(range, format!("Unknown owner {owner}")));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Warn about labels and assignees of an issue template that GitHub would drop
+    fn template_diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        // empty maps mean we could not list them
+        template::entries(text)
+            .into_iter()
+            .filter_map(|entry| {
+                // GitHub matches both case-insensitively
+                let value = entry.value.to_lowercase();
+                let (map_is_empty, known, kind) = match entry.field {
+                    Field::Labels => (
+                        self.label_map.is_empty(),
+                        self.label_map
+                            .iter()
+                            .any(|label| label.key().to_lowercase() == value),
+                        "label",
+                    ),
+                    Field::Assignees => (
+                        self.member_map.is_empty(),
+                        self.member_map
+                            .iter()
+                            .any(|member| member.key().to_lowercase() == value),
+                        "assignee",
+                    ),
+                };
+                (!map_is_empty && !known).then(|| Diagnostic {
+                    range: entry.range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    source: Some("github-lsp".into()),
+                    message: format!("Unknown {kind} {}", entry.value),
+                    ..Diagnostic::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Warn about `TODO(#123)` comments tracked by issues that are already closed
+    fn todo_diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        todo::todos(text)
+            .into_iter()
+            .filter_map(|todo| {
+                let issue = self.find_issue(todo.issue?)?;
+                matches!(issue.state, IssueState::Closed).then(|| Diagnostic {
+                    range: Range {
+                        start: todo.marker_end,
+                        end: todo.range.end,
+                    },
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    source: Some("github-lsp".into()),
+                    message: format!("{} is closed", issue.get_label()),
+                    ..Diagnostic::default()
+                })
+            })
+            .collect::<Vec<Diagnostic>>()
+    }
+
+    /// Issues are keyed by title, so look them up by number
+    pub(crate) fn find_issue(&self, number: u64) -> Option<Issue> {
+        self.issue_map
+            .iter()
+            .find(|issue| issue.number == number)
+            .map(|issue| issue.value().clone())
+            .or_else(|| {
+                self.issue_search_map
+                    .iter()
+                    .flat_map(|search| search.value().clone())
+                    .find(|issue| issue.number == number)
+            })
+    }
+
+    /// GitHub resolves `[[page name]]` and `[[Page-Name]]` to the same page
+    fn find_wiki_article(&self, page: &str) -> Option<String> {
+        let page = page.trim().replace('-', " ").to_lowercase();
+        self.wiki_map
+            .iter()
+            .find(|article| article.title.to_lowercase() == page)
+            .map(|article| article.key().to_owned())
+    }
+
+    pub(crate) async fn on_change(&self, params: TextDocumentItem) {
+        let rope = ropey::Rope::from_str(&params.text);
+        self.document_map
             .insert(params.uri.to_string(), rope.clone());
+        self.language_map
+            .insert(params.uri.to_string(), params.language_id.to_owned());
+        // only what git will commit refers to anything
+        let text = if self.is_commit_message(&params.uri) {
+            commit::strip_comments(&params.text)
+        } else {
+            params.text.to_owned()
+        };
+        let prose = self.is_commit_message(&params.uri)
+            || self.is_pull_request(&params.uri)
+            || markdown::is_markdown(params.uri.path());
+        self.reference_map.insert(
+            params.uri.to_string(),
+            reference::references(&text, &self.owner, &self.repo, prose),
+        );
+        let mut diagnostics = self.todo_diagnostics(&text);
+        if let Some(path) = self
+            .document_path(&params.uri)
+            .map(|path| git::to_slash(&path))
+            .filter(|path| markdown::is_markdown(path))
+        {
+            self.markdown_map
+                .insert(path, markdown::headings(&params.text));
+            diagnostics.append(&mut self.link_diagnostics(&params.uri, &params.text));
+        } else if self.is_wiki_document(&params.uri) {
+            self.reindex_wiki(&[params.uri.to_owned()]).await;
+            diagnostics.append(&mut self.wiki_link_diagnostics(&params.text));
+        } else if self.is_codeowners(&params.uri) {
+            diagnostics.append(&mut self.codeowners_diagnostics(&params.text).await);
+        }
+        if self.is_issue_template(&params.uri) {
+            diagnostics.append(&mut self.template_diagnostics(&params.text));
+        }
+        self.client
+            .publish_diagnostics(params.uri, diagnostics, None)
+            .await;
     }
 
     async fn initialize_repos_as(&self, affiliation: &str) {
@@ -367,18 +2179,159 @@ impl Backend {
         self.client
             .show_message(MessageType::INFO, "initializing wiki")
             .await;
-        let wikis = gh::wiki::find_wiki_articles(&self.owner, &self.repo).await;
+        // prefer a checkout the user already has next to the repo over our own clone
+        let checkout = self
+            .root
+            .parent()
+            .map(|parent| parent.join(format!("{}.wiki", self.repo)))
+            .filter(|checkout| checkout.is_dir());
+        let wiki_dir = match checkout {
+            Some(checkout) => checkout,
+            None => {
+                let Some(cache_dir) = gh::wiki::wiki_cache_dir(&self.owner, &self.repo) else {
+                    self.client
+                        .log_message(MessageType::WARNING, "No cache dir for wiki")
+                        .await;
+                    return;
+                };
+                if gh::wiki::clone_wiki(&self.owner, &self.repo, &cache_dir)
+                    .await
+                    .is_err()
+                {
+                    self.client
+                        .log_message(MessageType::WARNING, "No wiki found")
+                        .await;
+                    return;
+                }
+                cache_dir
+            }
+        };
+        self.index_wiki(&wiki_dir).await;
+        let _ = self.wiki_dir.set(wiki_dir);
+    }
+
+    async fn index_wiki(&self, wiki_dir: &Path) {
+        let wikis = gh::wiki::find_wiki_articles(&self.owner, &self.repo, wiki_dir).await;
         match wikis {
-            Ok(articles) => articles.into_iter().for_each(|article| {
-                self.wiki_map.insert(article.title.to_owned(), article);
-            }),
+            Ok(articles) => {
+                self.wiki_map.clear();
+                articles.into_iter().for_each(|article| {
+                    self.wiki_map.insert(article.title.to_owned(), article);
+                })
+            }
             Err(_) => {
                 self.client
                     .log_message(MessageType::WARNING, "No wiki found")
                     .await;
             }
         }
-        //TODO: load local .md files and make relative links?
+    }
+
+    /// Ask the client to tell us about pages added, renamed or removed in the wiki checkout
+    pub(crate) async fn watch_wiki(&self) {
+        let Some(wiki_dir) = self.wiki_dir.get() else {
+            return;
+        };
+        let can_register = self
+            .client_capabilities
+            .get()
+            .and_then(|capabilities| capabilities.workspace.as_ref())
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|watched_files| watched_files.dynamic_registration)
+            .unwrap_or_default();
+        if !can_register {
+            return;
+        }
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String(format!("{}/**/*.md", git::to_slash(wiki_dir))),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "github-lsp.watchWiki".into(),
+            method: "workspace/didChangeWatchedFiles".into(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            self.client.log_message(MessageType::WARNING, err).await;
+        }
+    }
+
+    /// Index the wiki checkout again when pages were added, renamed or removed on disk
+    pub(crate) async fn reindex_wiki(&self, changed: &[Url]) {
+        let Some(wiki_dir) = self.wiki_dir.get() else {
+            return;
+        };
+        let pages_changed = changed.iter().any(|uri| {
+            uri.to_file_path().is_ok_and(|path| {
+                path.starts_with(wiki_dir)
+                    && !self.wiki_map.iter().any(|article| article.path == path)
+            })
+        });
+        let pages_removed = self.wiki_map.iter().any(|article| !article.path.exists());
+        if pages_changed || pages_removed {
+            self.index_wiki(wiki_dir).await;
+        }
+    }
+
+    async fn initialize_markdown(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing markdown")
+            .await;
+        let Ok(files) = git::ls_files(&self.root).await else {
+            self.client
+                .log_message(MessageType::WARNING, "No markdown files found")
+                .await;
+            return;
+        };
+        for file in files.into_iter().filter(|file| markdown::is_markdown(file)) {
+            if let Ok(text) = tokio::fs::read_to_string(self.root.join(&file)).await {
+                self.markdown_map.insert(file, markdown::headings(&text));
+            }
+        }
+    }
+
+    async fn initialize_emojis(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing emojis")
+            .await;
+        match gh::emoji::find_emojis(&self.octocrab).await {
+            Ok(emojis) => emojis.into_iter().for_each(|emoji| {
+                self.emoji_map.insert(emoji.shortcode.to_owned(), emoji);
+            }),
+            Err(_) => {
+                self.client
+                    .log_message(MessageType::WARNING, "No emojis found")
+                    .await;
+            }
+        }
+    }
+
+    async fn initialize_references(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing references")
+            .await;
+        let Ok(files) = git::ls_files(&self.root).await else {
+            self.client
+                .log_message(MessageType::WARNING, "No files to find references in")
+                .await;
+            return;
+        };
+        for file in files {
+            let path = self.root.join(&file);
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            // binaries will not be valid UTF-8 and are skipped
+            if let Ok(text) = tokio::fs::read_to_string(&path).await {
+                let prose = markdown::is_markdown(&file);
+                self.reference_map.insert(
+                    uri.to_string(),
+                    reference::references(&text, &self.owner, &self.repo, prose),
+                );
+            }
+        }
     }
 
     async fn initialize_members(&self) {
@@ -409,7 +2362,107 @@ impl Backend {
             return;
         };
         members.into_iter().for_each(|member| {
+            self.org_member_map
+                .insert(member.login.to_owned(), member.clone());
             self.member_map.insert(member.login.to_owned(), member);
         });
     }
+
+    async fn initialize_collaborators(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing collaborators")
+            .await;
+        let mut page: u8 = 0;
+        let mut collaborators: Vec<Collaborator> = vec![];
+        while let Ok(mut page_collaborators) = self
+            .octocrab
+            .repos(&self.owner, &self.repo)
+            .list_collaborators()
+            .per_page(Backend::PER_PAGE)
+            .page(page)
+            .send()
+            .await
+        {
+            if page_collaborators.items.is_empty() {
+                break;
+            }
+            collaborators.append(page_collaborators.items.as_mut());
+            page += 1;
+        }
+        if collaborators.is_empty() {
+            self.client
+                .log_message(MessageType::WARNING, "No collaborators found")
+                .await;
+            return;
+        };
+        // collaborators outside the organization are members of the repo as far as we care
+        collaborators.into_iter().for_each(|collaborator| {
+            self.member_map
+                .insert(collaborator.author.login.to_owned(), collaborator.author);
+        });
+    }
+
+    async fn initialize_teams(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing teams")
+            .await;
+        let mut page: u8 = 0;
+        let mut teams: Vec<RequestedTeam> = vec![];
+        while let Ok(mut page_teams) = self
+            .octocrab
+            .teams(self.owner.to_owned())
+            .list()
+            .per_page(Backend::PER_PAGE)
+            .page(page)
+            .send()
+            .await
+        {
+            if page_teams.items.is_empty() {
+                break;
+            }
+            teams.append(page_teams.items.as_mut());
+            page += 1;
+        }
+        if teams.is_empty() {
+            self.client
+                .log_message(MessageType::WARNING, "No teams found")
+                .await;
+            return;
+        };
+        teams.into_iter().for_each(|team| {
+            self.team_map.insert(team.slug.to_owned(), team);
+        });
+    }
+
+    async fn initialize_labels(&self) {
+        self.client
+            .show_message(MessageType::INFO, "initializing labels")
+            .await;
+        let mut page: u8 = 0;
+        let mut labels: Vec<Label> = vec![];
+        while let Ok(mut page_labels) = self
+            .octocrab
+            .issues(self.owner.to_owned(), self.repo.to_owned())
+            .list_labels_for_repo()
+            .per_page(Backend::PER_PAGE)
+            .page(page)
+            .send()
+            .await
+        {
+            if page_labels.items.is_empty() {
+                break;
+            }
+            labels.append(page_labels.items.as_mut());
+            page += 1;
+        }
+        if labels.is_empty() {
+            self.client
+                .log_message(MessageType::WARNING, "No labels found")
+                .await;
+            return;
+        };
+        labels.into_iter().for_each(|label| {
+            self.label_map.insert(label.name.to_owned(), label);
+        });
+    }
 }
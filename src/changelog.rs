@@ -0,0 +1,137 @@
+/// Conventional commit types and the sections they are listed under, in order
+const SECTIONS: [(&str, &str); 10] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("docs", "Documentation"),
+    ("refactor", "Refactoring"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+/// A merged pull request, or a commit pushed without one
+#[derive(Debug, Clone)]
+pub(crate) struct Change {
+    pub title: String,
+    /// `([#12](...))` or `([abc1234](...))`
+    pub link: String,
+}
+
+/// `type(scope)!: description`, anything else has no type
+fn conventional(title: &str) -> Option<(&str, Option<&str>, bool, &str)> {
+    let (prefix, description) = title.split_once(": ")?;
+    let breaking = prefix.ends_with('!');
+    let prefix = prefix.trim_end_matches('!');
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+        None => (prefix, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((kind, scope, breaking, description))
+}
+
+/// The number of the pull request a commit on the default branch came from, either a merge
+/// commit or a squashed `title (#12)`
+pub fn pull_request(subject: &str) -> Option<u64> {
+    if let Some(rest) = subject.strip_prefix("Merge pull request #") {
+        return rest.split_whitespace().next()?.parse().ok();
+    }
+    let (_, number) = subject.strip_suffix(')')?.rsplit_once("(#")?;
+    number.parse().ok()
+}
+
+/// The title of a squashed commit without its `(#12)`
+pub fn strip_pull_request(subject: &str) -> &str {
+    match subject.rsplit_once(" (#") {
+        Some((title, number)) if pull_request(subject).is_some() && number.ends_with(')') => title,
+        _ => subject,
+    }
+}
+
+/// A Markdown section like the ones in our own CHANGELOG.md, grouped by conventional commit type
+pub fn render(heading: &str, compare: &str, changes: &[Change]) -> String {
+    let mut groups: Vec<(&str, Vec<String>)> = vec![("Breaking Changes", vec![])];
+    groups.extend(SECTIONS.iter().map(|(_, section)| (*section, vec![])));
+    groups.push(("Other", vec![]));
+    for change in changes {
+        let (section, item) = match conventional(&change.title) {
+            Some((kind, scope, breaking, description)) => {
+                let section = if breaking {
+                    "Breaking Changes"
+                } else {
+                    SECTIONS
+                        .iter()
+                        .find(|(known, _)| *known == kind)
+                        .map(|(_, section)| *section)
+                        .unwrap_or("Other")
+                };
+                let item = match scope {
+                    Some(scope) => format!("**{scope}:** {description}"),
+                    None => description.to_owned(),
+                };
+                (section, item)
+            }
+            None => ("Other", change.title.to_owned()),
+        };
+        if let Some((_, items)) = groups.iter_mut().find(|(known, _)| *known == section) {
+            items.push(format!("- {item} {}", change.link));
+        }
+    }
+    let mut changelog = format!("# {heading}\n\n[Full changelog]({compare})\n");
+    groups
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .for_each(|(section, items)| {
+            changelog.push_str(&format!("\n## {section}\n\n{}\n", items.join("\n")));
+        });
+    changelog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(title: &str, number: u64) -> Change {
+        Change {
+            title: title.into(),
+            link: format!("(#{number})"),
+        }
+    }
+
+    #[test]
+    fn finds_the_pull_request_of_a_commit() {
+        assert_eq!(
+            pull_request("Merge pull request #12 from octo/branch"),
+            Some(12)
+        );
+        assert_eq!(pull_request("feat: hover (#34)"), Some(34));
+        assert_eq!(pull_request("feat: hover"), None);
+        assert_eq!(strip_pull_request("feat: hover (#34)"), "feat: hover");
+        assert_eq!(strip_pull_request("fix: (#x) parsing"), "fix: (#x) parsing");
+    }
+
+    #[test]
+    fn groups_changes_by_type() {
+        let changes = [
+            change("feat(lsp): add hover", 1),
+            change("fix!: drop the old API", 2),
+            change("Update readme", 3),
+            change("chore: bump deps", 4),
+            change("feat: add rename", 5),
+            change("wip(: broken", 6),
+        ];
+        assert_eq!(
+            render("v1.1.0", "https://example.com/compare", &changes),
+            "# v1.1.0\n\n[Full changelog](https://example.com/compare)\n\
+             \n## Breaking Changes\n\n- drop the old API (#2)\n\
+             \n## Features\n\n- **lsp:** add hover (#1)\n- add rename (#5)\n\
+             \n## Chores\n\n- bump deps (#4)\n\
+             \n## Other\n\n- Update readme (#3)\n- wip(: broken (#6)\n"
+        );
+    }
+}
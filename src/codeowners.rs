@@ -0,0 +1,153 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+/// A `pattern @owner @org/team user@example.com` line
+#[derive(Debug, Clone)]
+pub(crate) struct Rule {
+    pub pattern: String,
+    pub range: Range,
+    pub owners: Vec<(String, Range)>,
+}
+
+pub fn is_codeowners(path: &str) -> bool {
+    path == "CODEOWNERS" || path.ends_with("/CODEOWNERS")
+}
+
+/// Every rule in a CODEOWNERS file, skipping comments
+pub fn rules(text: &str) -> Vec<Rule> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line_number, line)| {
+            let line = line.split(" #").next().unwrap_or_default();
+            let character = |index: usize| line[..index].chars().count() as u32;
+            let range = |start: usize, token: &str| Range {
+                start: Position {
+                    line: line_number as u32,
+                    character: character(start),
+                },
+                end: Position {
+                    line: line_number as u32,
+                    character: character(start + token.len()),
+                },
+            };
+            let mut tokens = line
+                .split_whitespace()
+                .map(|token| (token.as_ptr() as usize - line.as_ptr() as usize, token));
+            let (start, pattern) = tokens.next()?;
+            if pattern.starts_with('#') {
+                return None;
+            }
+            Some(Rule {
+                pattern: pattern.to_owned(),
+                range: range(start, pattern),
+                owners: tokens
+                    .map(|(start, owner)| (owner.to_owned(), range(start, owner)))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// `*` and `?` within a path segment, `**` across them
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        // zero or more whole directories
+        ['*', '*', '/', rest @ ..] => {
+            glob(rest, text)
+                || text
+                    .iter()
+                    .position(|ch| *ch == '/')
+                    .is_some_and(|slash| glob(pattern, &text[slash + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|skip| glob(rest, &text[skip..])),
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|skip| *skip == 0 || text[skip - 1] != '/')
+            .any(|skip| glob(rest, &text[skip..])),
+        ['?', rest @ ..] => text.first().is_some_and(|ch| *ch != '/') && glob(rest, &text[1..]),
+        [ch, rest @ ..] => text.first() == Some(ch) && glob(rest, &text[1..]),
+    }
+}
+
+/// Whether a gitignore style pattern covers the repository relative `path` of a file
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    // patterns with a slash other than at the end are relative to the root
+    let anchored = pattern.contains('/');
+    let pattern = pattern
+        .trim_start_matches('/')
+        .chars()
+        .collect::<Vec<char>>();
+    let components = path.split('/').collect::<Vec<&str>>();
+    let last = components.len();
+    if anchored {
+        (1..=last)
+            .filter(|len| !dir_only || *len < last)
+            .any(|len| {
+                glob(
+                    &pattern,
+                    &components[..len].join("/").chars().collect::<Vec<char>>(),
+                )
+            })
+    } else {
+        components
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !dir_only || index + 1 < last)
+            .any(|(_, component)| glob(&pattern, &component.chars().collect::<Vec<char>>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_unanchored_patterns_anywhere() {
+        assert!(matches("*.rs", "src/main.rs"));
+        assert!(matches("main.rs", "src/main.rs"));
+        assert!(!matches("*.rs", "src/main.rsx"));
+        assert!(matches("docs/", "docs/index.md"));
+        assert!(!matches("docs/", "docs"));
+    }
+
+    #[test]
+    fn matches_anchored_patterns_from_the_root() {
+        assert!(matches("/src/", "src/gh/mod.rs"));
+        assert!(!matches("/gh/", "src/gh/mod.rs"));
+        assert!(matches("src/*.rs", "src/main.rs"));
+        assert!(!matches("src/*.rs", "src/gh/mod.rs"));
+        assert!(matches("src/?ain.rs", "src/main.rs"));
+    }
+
+    #[test]
+    fn double_stars_cross_whole_directories() {
+        assert!(matches("**/foo", "foo"));
+        assert!(matches("**/foo", "a/b/foo"));
+        assert!(!matches("**/foo", "barfoo"));
+        assert!(matches("/docs/**/foo", "docs/foo"));
+        assert!(matches("/docs/**/foo", "docs/a/b/foo"));
+        assert!(!matches("/docs/**/foo", "docs/xfoo"));
+        assert!(matches("src/**", "src/gh/mod.rs"));
+    }
+
+    #[test]
+    fn rules_skip_comments_and_keep_ranges() {
+        let rules =
+            rules("# owners\n*.rs @octocat @org/team # trailing\n\n/docs/ docs@example.com\n");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].pattern, "*.rs");
+        assert_eq!(
+            rules[0]
+                .owners
+                .iter()
+                .map(|(owner, _)| owner.as_str())
+                .collect::<Vec<&str>>(),
+            ["@octocat", "@org/team"]
+        );
+        assert_eq!(rules[0].owners[1].1.start, Position::new(1, 14));
+        assert_eq!(rules[0].owners[1].1.end, Position::new(1, 23));
+        assert_eq!(rules[1].pattern, "/docs/");
+        assert_eq!(rules[1].range.start, Position::new(3, 0));
+    }
+}
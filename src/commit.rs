@@ -0,0 +1,37 @@
+/// Keywords that close the issue they precede once the commit lands on the default branch
+const CLOSING_KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+pub const CO_AUTHORED_BY: &str = "Co-authored-by: ";
+
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+pub fn is_closing_keyword(word: &str) -> bool {
+    let word = word.trim_end_matches(':').to_lowercase();
+    CLOSING_KEYWORDS.contains(&word.as_str())
+}
+
+/// The start of a line that is becoming a `Co-authored-by: ` trailer
+pub fn is_trailer_start(line: &str) -> bool {
+    let line = line.to_lowercase();
+    let trailer = CO_AUTHORED_BY.to_lowercase();
+    line.len() >= 2 && (trailer.starts_with(&line) || line.starts_with(&trailer))
+}
+
+/// The message git will actually commit, with `#` comments and everything below the scissors
+/// blanked out so that line numbers stay the same
+pub fn strip_comments(text: &str) -> String {
+    let mut scissors = false;
+    text.lines()
+        .map(|line| {
+            scissors = scissors || line == SCISSORS;
+            if scissors || line.starts_with('#') {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
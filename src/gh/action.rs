@@ -0,0 +1,179 @@
+use octocrab::Octocrab;
+use serde_yaml::Value;
+
+use super::GetDetail;
+
+/// Actions most workflows use, suggested before anyone has to search for them
+pub const POPULAR_ACTIONS: [&str; 16] = [
+    "actions/cache",
+    "actions/checkout",
+    "actions/configure-pages",
+    "actions/deploy-pages",
+    "actions/download-artifact",
+    "actions/github-script",
+    "actions/setup-go",
+    "actions/setup-java",
+    "actions/setup-node",
+    "actions/setup-python",
+    "actions/upload-artifact",
+    "actions/upload-pages-artifact",
+    "docker/build-push-action",
+    "docker/login-action",
+    "dtolnay/rust-toolchain",
+    "github/codeql-action",
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct ActionInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub required: bool,
+    pub default: Option<String>,
+}
+
+/// The metadata of an action from its `action.yml`
+#[derive(Debug, Clone)]
+pub(crate) struct Action {
+    pub uses: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub inputs: Vec<ActionInput>,
+}
+
+impl GetDetail for Action {
+    fn get_detail(&self) -> String {
+        let mut detail = format!("# {}\n\n", self.name.as_ref().unwrap_or(&self.uses));
+        if let Some(description) = &self.description {
+            detail.push_str(&format!("{description}\n\n"));
+        }
+        if !self.inputs.is_empty() {
+            detail.push_str("## Inputs\n\n");
+            self.inputs.iter().for_each(|input| {
+                let required = if input.required { " (required)" } else { "" };
+                detail.push_str(&format!("- `{}`{required}", input.name));
+                if let Some(description) = &input.description {
+                    detail.push_str(&format!(": {}", description.trim()));
+                }
+                if let Some(default) = &input.default {
+                    detail.push_str(&format!(" Default `{default}`."));
+                }
+                detail.push('\n');
+            });
+        }
+        detail
+    }
+}
+
+/// A `uses:` value, `owner/repo[/path]@ref`, `./local/path` or `docker://image`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Uses {
+    Remote {
+        owner: String,
+        repo: String,
+        path: String,
+        reference: String,
+    },
+    Local(String),
+    Docker,
+}
+
+impl Uses {
+    pub fn parse(uses: &str) -> Option<Uses> {
+        if uses.starts_with("docker://") {
+            return Some(Uses::Docker);
+        }
+        if uses.starts_with("./") {
+            return Some(Uses::Local(uses.trim_start_matches("./").to_owned()));
+        }
+        let (action, reference) = uses.split_once('@').unwrap_or((uses, ""));
+        let mut parts = action.splitn(3, '/');
+        let (owner, repo) = (parts.next()?, parts.next()?);
+        Some(Uses::Remote {
+            owner: owner.to_owned(),
+            repo: repo.to_owned(),
+            path: parts.next().unwrap_or_default().to_owned(),
+            reference: reference.to_owned(),
+        })
+    }
+}
+
+fn string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.to_owned()),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+pub fn parse_action(uses: &str, yaml: &str) -> Option<Action> {
+    let action: Value = serde_yaml::from_str(yaml).ok()?;
+    let inputs = action["inputs"]
+        .as_mapping()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, input)| {
+            Some(ActionInput {
+                name: string(name)?,
+                description: string(&input["description"]),
+                required: string(&input["required"]).is_some_and(|required| required == "true"),
+                default: string(&input["default"]),
+            })
+        })
+        .collect();
+    Some(Action {
+        uses: uses.to_owned(),
+        name: string(&action["name"]),
+        description: string(&action["description"]),
+        inputs,
+    })
+}
+
+/// The `action.yml` (or `action.yaml`) of a remote action at the referenced version
+pub async fn find_action(
+    octocrab: &Octocrab,
+    uses: &str,
+    owner: &str,
+    repo: &str,
+    path: &str,
+    reference: &str,
+) -> Option<Action> {
+    for file in ["action.yml", "action.yaml"] {
+        let file = if path.is_empty() {
+            file.to_owned()
+        } else {
+            format!("{path}/{file}")
+        };
+        let handler = octocrab.repos(owner, repo);
+        let mut content = handler.get_content().path(file);
+        if !reference.is_empty() {
+            content = content.r#ref(reference);
+        }
+        let Ok(mut items) = content.send().await else {
+            continue;
+        };
+        if let Some(yaml) = items
+            .take_items()
+            .first()
+            .and_then(|item| item.decoded_content())
+        {
+            return parse_action(uses, &yaml);
+        }
+    }
+    None
+}
+
+/// Tags are what most workflows pin an action to
+pub async fn find_action_tags(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+) -> octocrab::Result<Vec<String>> {
+    let tags = octocrab
+        .repos(owner, repo)
+        .list_tags()
+        .per_page(100)
+        .send()
+        .await?;
+    Ok(tags.items.into_iter().map(|tag| tag.name).collect())
+}
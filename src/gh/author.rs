@@ -1,4 +1,7 @@
 use octocrab::models::Author;
+use octocrab::Octocrab;
+
+use crate::commit::CO_AUTHORED_BY;
 
 use super::{GetDetail, GetEdit, GetLabel};
 
@@ -18,3 +21,32 @@ impl GetDetail for Author {
         self.get_label()
     }
 }
+
+/// A `Co-authored-by: ` trailer GitHub attributes to the author, by their noreply address
+pub fn co_authored_by(author: &Author, name: Option<&str>) -> String {
+    let login = &author.login;
+    let id = author.id;
+    let name = name.unwrap_or(login);
+    format!("{CO_AUTHORED_BY}{name} <{id}+{login}@users.noreply.github.com>")
+}
+
+/// The authors of the latest commits to `path` on the default branch, most recent first
+pub async fn find_contributors(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    path: &str,
+) -> octocrab::Result<Vec<Author>> {
+    let commits = octocrab
+        .repos(owner, repo)
+        .list_commits()
+        .path(path)
+        .per_page(20u8)
+        .send()
+        .await?;
+    Ok(commits
+        .items
+        .into_iter()
+        .filter_map(|commit| commit.author)
+        .collect())
+}
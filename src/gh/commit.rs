@@ -0,0 +1,39 @@
+use octocrab::models::repos::RepoCommit;
+
+use super::{GetDetail, GetLabel};
+
+impl GetLabel for RepoCommit {
+    fn get_label(&self) -> String {
+        let subject = self.commit.message.lines().next().unwrap_or_default();
+        format!("{} {}", &self.sha[..7.min(self.sha.len())], subject)
+    }
+}
+impl GetDetail for RepoCommit {
+    fn get_detail(&self) -> String {
+        let mut detail = format!("# {}\n\n", self.get_label());
+        let author = self
+            .author
+            .as_ref()
+            .map(|author| format!("@{}", author.login))
+            .or(self
+                .commit
+                .author
+                .as_ref()
+                .map(|author| author.name.to_owned()));
+        let date = self
+            .commit
+            .author
+            .as_ref()
+            .and_then(|author| author.date)
+            .map(|date| date.format(" on %Y-%m-%d").to_string())
+            .unwrap_or_default();
+        if let Some(author) = author {
+            detail.push_str(&format!("Committed by {author}{date}\n\n"));
+        }
+        if let Some((_, body)) = self.commit.message.split_once('\n') {
+            detail.push_str(&format!("{}\n\n", body.trim()));
+        }
+        detail.push_str(&format!("[{}]({})", self.sha, self.html_url));
+        detail
+    }
+}
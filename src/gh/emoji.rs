@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use octocrab::Octocrab;
+
+use super::{GetDetail, GetEdit, GetLabel};
+
+#[derive(Debug)]
+pub(crate) struct Emoji {
+    pub shortcode: String,
+    pub url: String,
+}
+
+impl GetLabel for Emoji {
+    fn get_label(&self) -> String {
+        format!(":{}:", self.shortcode)
+    }
+}
+impl GetEdit for Emoji {
+    fn get_edit(&self) -> String {
+        self.get_label()
+    }
+}
+impl GetDetail for Emoji {
+    fn get_detail(&self) -> String {
+        format!("![{}]({})", self.get_label(), self.url)
+    }
+}
+
+pub async fn find_emojis(octocrab: &Octocrab) -> octocrab::Result<Vec<Emoji>> {
+    // GitHub only serves the shortcodes as image URLs, not the unicode characters
+    let emojis: HashMap<String, String> = octocrab.get("/emojis", None::<&()>).await?;
+    Ok(emojis
+        .into_iter()
+        .map(|(shortcode, url)| Emoji { shortcode, url })
+        .collect())
+}
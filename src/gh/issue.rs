@@ -1,6 +1,24 @@
-use octocrab::models::{issues::Issue, IssueState};
+use octocrab::models::issues::{Comment, Issue};
+use octocrab::models::IssueState;
+use octocrab::Octocrab;
+use serde_json::Value;
 
-use super::{GetDetail, GetEdit, GetLabel};
+use super::{time_ago, GetDetail, GetEdit, GetLabel};
+
+/// How many of the latest comments an issue hover shows
+const RECENT_COMMENTS: usize = 3;
+
+/// GitHub's reaction names and how it shows them
+const REACTIONS: [(&str, &str); 8] = [
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("laugh", "😄"),
+    ("hooray", "🎉"),
+    ("confused", "😕"),
+    ("heart", "❤️"),
+    ("rocket", "🚀"),
+    ("eyes", "👀"),
+];
 
 impl GetLabel for IssueState {
     fn get_label(&self) -> String {
@@ -38,12 +56,160 @@ impl GetEdit for Issue {
 impl GetDetail for Issue {
     fn get_detail(&self) -> String {
         let title = self.title.to_string();
-        format!(
-            "# {} [{}] {}\n\n{}",
+        let mut detail = format!(
+            "# {} [{}] {}\n\n",
             self.number,
             self.state.get_label(),
             title,
-            self.body.as_ref().unwrap_or(&title)
+        );
+        detail.push_str(&format!(
+            "**Author:** @{}, opened {}, updated {}  \n",
+            self.user.login,
+            time_ago(self.created_at.timestamp()),
+            time_ago(self.updated_at.timestamp())
+        ));
+        if let Some(milestone) = &self.milestone {
+            detail.push_str(&format!("**Milestone:** {}  \n", milestone.title));
+        }
+        if !self.labels.is_empty() {
+            let labels = self
+                .labels
+                .iter()
+                .map(|label| format!("`{}`", label.name))
+                .collect::<Vec<String>>()
+                .join(", ");
+            detail.push_str(&format!("**Labels:** {labels}  \n"));
+        }
+        if !self.assignees.is_empty() {
+            let assignees = self
+                .assignees
+                .iter()
+                .map(|assignee| format!("@{}", assignee.login))
+                .collect::<Vec<String>>()
+                .join(", ");
+            detail.push_str(&format!("**Assignees:** {assignees}  \n"));
+        }
+        detail.push_str(&format!("**Comments:** {}\n\n", self.comments));
+        detail.push_str(self.body.as_ref().unwrap_or(&title));
+        detail
+    }
+}
+
+/// What happened around an issue since it was opened, which the issue itself does not include
+#[derive(Debug, Clone)]
+pub(crate) struct IssueActivity {
+    pub reactions: Vec<(String, u64)>,
+    /// number, title and link of the pull requests that mention the issue
+    pub linked_pulls: Vec<(u64, String, String)>,
+    pub comments: Vec<Comment>,
+}
+
+impl GetDetail for IssueActivity {
+    fn get_detail(&self) -> String {
+        let mut detail = String::new();
+        if !self.reactions.is_empty() {
+            let reactions = self
+                .reactions
+                .iter()
+                .map(|(emoji, count)| format!("{emoji} {count}"))
+                .collect::<Vec<String>>()
+                .join(" ");
+            detail.push_str(&format!("\n\n{reactions}"));
+        }
+        if !self.linked_pulls.is_empty() {
+            detail.push_str("\n\n## Linked pull requests\n");
+            self.linked_pulls.iter().for_each(|(number, title, url)| {
+                detail.push_str(&format!("\n- [#{number}]({url}) {title}"));
+            });
+        }
+        if !self.comments.is_empty() {
+            detail.push_str("\n\n## Recent comments");
+            self.comments.iter().for_each(|comment| {
+                let body = comment
+                    .body
+                    .as_deref()
+                    .unwrap_or_default()
+                    .lines()
+                    .map(|line| format!("> {line}"))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                detail.push_str(&format!(
+                    "\n\n**@{}** commented {}\n\n{body}",
+                    comment.user.login,
+                    time_ago(comment.created_at.timestamp())
+                ));
+            });
+        }
+        detail
+    }
+}
+
+/// Reactions, pull requests mentioning the issue and its latest comments
+pub async fn find_issue_activity(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    issue: &Issue,
+) -> octocrab::Result<IssueActivity> {
+    let number = issue.number;
+    let raw: Value = octocrab
+        .get(
+            format!("/repos/{owner}/{repo}/issues/{number}"),
+            None::<&()>,
         )
+        .await?;
+    let reactions = REACTIONS
+        .iter()
+        .filter_map(|(name, emoji)| {
+            let count = raw["reactions"][name].as_u64()?;
+            (count > 0).then(|| (emoji.to_string(), count))
+        })
+        .collect();
+    let timeline: Value = octocrab
+        .get(
+            format!("/repos/{owner}/{repo}/issues/{number}/timeline"),
+            Some(&[("per_page", "100")]),
+        )
+        .await?;
+    let linked_pulls = timeline
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|event| event["event"] == "cross-referenced")
+        .map(|event| &event["source"]["issue"])
+        .filter(|source| !source["pull_request"].is_null())
+        .filter_map(|source| {
+            Some((
+                source["number"].as_u64()?,
+                source["title"].as_str()?.to_owned(),
+                source["html_url"].as_str()?.to_owned(),
+            ))
+        })
+        .collect();
+    // the latest comments are on the last page, or also the one before when that is short. The
+    // issue we were given may be from startup, so count the comments of the fresh one
+    let count = raw["comments"].as_u64().unwrap_or(issue.comments as u64);
+    let last_page = count.div_ceil(100).max(1) as u32;
+    let mut comments: Vec<Comment> = vec![];
+    for page in (1..=last_page).rev().take(2) {
+        let mut page_comments = octocrab
+            .issues(owner, repo)
+            .list_comments(number)
+            .per_page(100)
+            .page(page)
+            .send()
+            .await?
+            .items;
+        page_comments.append(&mut comments);
+        comments = page_comments;
+        if comments.len() >= RECENT_COMMENTS {
+            break;
+        }
     }
+    let comments = comments.split_off(comments.len().saturating_sub(RECENT_COMMENTS));
+    Ok(IssueActivity {
+        reactions,
+        linked_pulls,
+        comments,
+    })
 }
@@ -0,0 +1,24 @@
+use octocrab::models::Label;
+
+use super::{GetDetail, GetEdit, GetLabel};
+
+impl GetLabel for Label {
+    fn get_label(&self) -> String {
+        self.name.to_owned()
+    }
+}
+impl GetEdit for Label {
+    fn get_edit(&self) -> String {
+        self.name.to_owned()
+    }
+}
+impl GetDetail for Label {
+    fn get_detail(&self) -> String {
+        format!(
+            "# {}\n\n{}\n\nColor `#{}`",
+            self.name,
+            self.description.as_deref().unwrap_or_default(),
+            self.color
+        )
+    }
+}
@@ -1,6 +1,11 @@
-mod author;
-mod issue;
-mod repo;
+pub(crate) mod action;
+pub(crate) mod author;
+mod commit;
+pub(crate) mod emoji;
+pub(crate) mod issue;
+mod label;
+pub(crate) mod repo;
+pub(crate) mod user;
 pub(crate) mod wiki;
 
 use std::fmt;
@@ -70,6 +75,25 @@ pub async fn gh_cli_owner_name() -> std::result::Result<(String, String), GitHub
         .ok_or(GitHubCLIError::NoOwner)
 }
 
+/// `3 days ago` for a unix timestamp, the way GitHub shows when something happened
+pub fn time_ago(timestamp: i64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or(timestamp);
+    let seconds = (now - timestamp).max(0);
+    let (count, unit) = match seconds {
+        0..60 => return "just now".into(),
+        60..3_600 => (seconds / 60, "minute"),
+        3_600..86_400 => (seconds / 3_600, "hour"),
+        86_400..2_592_000 => (seconds / 86_400, "day"),
+        2_592_000..31_536_000 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
 pub(crate) trait GetLabel {
     fn get_label(&self) -> String;
 }
@@ -1,4 +1,6 @@
 use octocrab::models::Repository;
+use octocrab::Octocrab;
+use serde_json::Value;
 
 use super::{GetDetail, GetEdit, GetLabel};
 
@@ -29,3 +31,24 @@ impl GetDetail for Repository {
         format!("{}\n{}", self.get_edit(), description)
     }
 }
+
+pub async fn find_tree_paths(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    reference: &str,
+) -> octocrab::Result<Vec<String>> {
+    let tree: Value = octocrab
+        .get(
+            format!("/repos/{owner}/{repo}/git/trees/{reference}"),
+            Some(&[("recursive", "1")]),
+        )
+        .await?;
+    Ok(tree["tree"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry["type"] == "blob")
+        .filter_map(|entry| entry["path"].as_str().map(String::from))
+        .collect())
+}
@@ -0,0 +1,41 @@
+use octocrab::models::orgs::Organization;
+use octocrab::models::UserProfile;
+use octocrab::Octocrab;
+
+use super::GetDetail;
+
+impl GetDetail for UserProfile {
+    fn get_detail(&self) -> String {
+        let mut detail = match &self.name {
+            Some(name) => format!("# {name} ({})\n\n", self.login),
+            None => format!("# {}\n\n", self.login),
+        };
+        // the full size avatar would fill the whole hover
+        let mut avatar_url = self.avatar_url.clone();
+        avatar_url.query_pairs_mut().append_pair("s", "64");
+        detail.push_str(&format!("![{}]({avatar_url})\n\n", self.login));
+        if let Some(bio) = self.bio.as_ref().filter(|bio| !bio.is_empty()) {
+            detail.push_str(&format!("{}\n\n", bio.trim()));
+        }
+        if let Some(company) = self.company.as_ref().filter(|company| !company.is_empty()) {
+            detail.push_str(&format!("Company: {company}\n\n"));
+        }
+        if let Some(location) = self
+            .location
+            .as_ref()
+            .filter(|location| !location.is_empty())
+        {
+            detail.push_str(&format!("Location: {location}\n\n"));
+        }
+        detail.push_str(&format!("[{}]({})", self.html_url.path(), self.html_url));
+        detail
+    }
+}
+
+/// The public organization memberships of a user
+pub async fn find_orgs(octocrab: &Octocrab, login: &str) -> octocrab::Result<Vec<String>> {
+    let orgs: Vec<Organization> = octocrab
+        .get(format!("/users/{login}/orgs"), None::<&()>)
+        .await?;
+    Ok(orgs.into_iter().map(|org| org.login).collect())
+}
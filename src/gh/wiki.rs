@@ -1,12 +1,19 @@
-use reqwest::Client;
-use select::{document::Document, predicate::Name};
+use std::path::{Path, PathBuf};
 
-use super::GetEdit;
+use crate::git::{self, GitCLIError};
+use crate::markdown::{self, Heading};
+
+use super::{GetDetail, GetEdit};
 
 #[derive(Debug)]
 pub(crate) struct WikiArticle {
     pub title: String,
     pub uri: String,
+    pub path: PathBuf,
+    pub headings: Vec<Heading>,
+    pub summary: String,
+    /// fetched when first hovered
+    pub last_edited: Option<String>,
 }
 
 impl GetEdit for WikiArticle {
@@ -17,34 +24,82 @@ impl GetEdit for WikiArticle {
     }
 }
 
+impl GetDetail for WikiArticle {
+    fn get_detail(&self) -> String {
+        let mut detail = format!("# {}\n\n{}\n", self.title, self.summary);
+        if !self.headings.is_empty() {
+            detail.push_str("\n## Contents\n\n");
+            self.headings.iter().for_each(|heading| {
+                detail.push_str(&format!("- {}\n", heading.title));
+            });
+        }
+        if let Some(last_edited) = &self.last_edited {
+            detail.push_str(&format!("\n_Last edited by {last_edited}_\n"));
+        }
+        detail
+    }
+}
+
+/// Where we keep our own clone of a wiki, unless one is checked out next to the repo
+pub fn wiki_cache_dir(owner: &str, repo: &str) -> Option<PathBuf> {
+    let cache = std::env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| Path::new(&home).join(".cache")))
+        .ok()?;
+    Some(
+        cache
+            .join("github-lsp")
+            .join(owner)
+            .join(format!("{repo}.wiki")),
+    )
+}
+
+/// Clone or update the wiki repo, `gh` provides the credentials so private wikis work too
+pub async fn clone_wiki(owner: &str, repo: &str, wiki_dir: &Path) -> Result<(), GitCLIError> {
+    let url = format!("https://github.com/{owner}/{repo}.wiki.git");
+    git::clone_or_pull(&url, wiki_dir).await
+}
+
+/// Who last changed a page and how long ago, e.g. `octocat, 2 days ago`
+pub async fn find_last_edited(article: &Path) -> Result<String, GitCLIError> {
+    let dir = article.parent().ok_or(GitCLIError::NoRepo)?;
+    git::last_edited(dir, article).await
+}
+
+/// Every page in a wiki checkout, titled the way GitHub renders the file name
 pub async fn find_wiki_articles(
     owner: &str,
     repo: &str,
-) -> Result<Vec<WikiArticle>, reqwest::Error> {
-    let client = Client::new();
-    //TODO: find a way to support private wikis?
-    let url = format!("https://github.com/{owner}/{repo}/wiki");
-    let res = client.get(url).send().await?;
-    let body = res.text().await?;
-    let mut ret: Vec<WikiArticle> = Document::from(body.as_str())
-        .find(Name("a"))
-        .filter(|a| a.attr("href").is_some())
-        .filter(|a| {
-            let href = a.attr("href").unwrap();
-            // GitHub renders wiki as relative links
-            // /$owner/$repo/$page
-            // We do not want wiki/_new et.al.
-            href.starts_with('/') && href.contains("wiki/") && !href.contains("/_")
-        })
-        .map(|link| WikiArticle {
-            title: link.text(),
-            uri: link.attr("href").unwrap().to_string(),
-        })
-        .collect();
-    ret.push(WikiArticle {
-        title: "Home".into(),
-        uri: format!("/{owner}/{repo}/wiki"),
-    });
-
-    Ok(ret)
+    wiki_dir: &Path,
+) -> Result<Vec<WikiArticle>, GitCLIError> {
+    let mut articles: Vec<WikiArticle> = vec![];
+    for file in git::ls_files(wiki_dir).await? {
+        let path = wiki_dir.join(&file);
+        let Some(page) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        // GitHub serves pages in subdirectories by their file name too, and _Sidebar/_Footer
+        // are not pages
+        if !markdown::is_markdown(&file) || page.starts_with('_') {
+            continue;
+        }
+        let text = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+        let uri = if page == "Home" {
+            format!("/{owner}/{repo}/wiki")
+        } else {
+            format!("/{owner}/{repo}/wiki/{page}")
+        };
+        articles.push(WikiArticle {
+            title: page.replace('-', " "),
+            uri,
+            path,
+            headings: markdown::headings(&text),
+            summary: markdown::first_paragraph(&text),
+            last_edited: None,
+        });
+    }
+    Ok(articles)
 }
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use tokio::process::Command;
+
+#[derive(Debug)]
+pub enum GitCLIError {
+    NoRepo,
+    NoRef,
+    NotUTF8,
+}
+impl std::error::Error for GitCLIError {}
+impl fmt::Display for GitCLIError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GitCLIError::NoRepo => write!(f, "No git repo found"),
+            GitCLIError::NoRef => write!(f, "No such git ref found"),
+            GitCLIError::NotUTF8 => write!(f, "No valid UTF-8 found"),
+        }
+    }
+}
+
+async fn git(root: &Path, args: &[&str]) -> Result<String, GitCLIError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .await
+        .map_err(|_| GitCLIError::NoRepo)?;
+    if !output.status.success() {
+        return Err(GitCLIError::NoRef);
+    }
+    String::from_utf8(output.stdout).map_err(|_| GitCLIError::NotUTF8)
+}
+
+pub async fn git_toplevel() -> Result<PathBuf, GitCLIError> {
+    let cwd = std::env::current_dir().map_err(|_| GitCLIError::NoRepo)?;
+    let toplevel = git(&cwd, &["rev-parse", "--show-toplevel"])
+        .await
+        .map_err(|_| GitCLIError::NoRepo)?;
+    Ok(PathBuf::from(toplevel.trim()))
+}
+
+/// Tracked and untracked files in the working tree, respecting `.gitignore`
+pub async fn ls_files(root: &Path) -> Result<Vec<String>, GitCLIError> {
+    let files = git(
+        root,
+        &["ls-files", "--cached", "--others", "--exclude-standard"],
+    )
+    .await?;
+    Ok(files.lines().map(String::from).collect())
+}
+
+/// Files in the tree of `reference`, which must be known to the local repo
+pub async fn ls_tree(root: &Path, reference: &str) -> Result<Vec<String>, GitCLIError> {
+    let files = git(root, &["ls-tree", "-r", "--name-only", reference]).await?;
+    Ok(files.lines().map(String::from).collect())
+}
+
+/// The branch `origin/HEAD` points to, where pull requests go by default
+pub async fn default_branch(root: &Path) -> Result<String, GitCLIError> {
+    let branch = git(
+        root,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    )
+    .await?;
+    Ok(branch.trim().to_owned())
+}
+
+/// Files the current branch changed since it forked from `base`
+pub async fn changed_files(root: &Path, base: &str) -> Result<Vec<String>, GitCLIError> {
+    let files = git(root, &["diff", "--name-only", &format!("{base}...HEAD")]).await?;
+    Ok(files.lines().map(String::from).collect())
+}
+
+/// The sha, subject and body of the commits in `from..to` that landed on the branch itself, so
+/// merged pull requests show up as their merge commit only
+pub async fn first_parent_log(
+    root: &Path,
+    from: &str,
+    to: &str,
+) -> Result<Vec<(String, String, String)>, GitCLIError> {
+    let log = git(
+        root,
+        &[
+            "log",
+            "--first-parent",
+            "--format=%H%x1f%s%x1f%b%x1e",
+            &format!("{from}..{to}"),
+        ],
+    )
+    .await?;
+    Ok(log
+        .split('\x1e')
+        .filter_map(|commit| {
+            let mut fields = commit.trim_start().splitn(3, '\x1f');
+            let (sha, subject) = (fields.next()?, fields.next()?);
+            let body = fields.next().unwrap_or_default();
+            (!sha.is_empty()).then(|| (sha.to_owned(), subject.to_owned(), body.trim().to_owned()))
+        })
+        .collect())
+}
+
+/// Express `path` relative to the directory `base`, both relative to the same root
+pub fn relative_path(base: &Path, path: &Path) -> String {
+    let base = base.components().collect::<Vec<Component>>();
+    let path = path.components().collect::<Vec<Component>>();
+    let common = base
+        .iter()
+        .zip(path.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = PathBuf::new();
+    base[common..].iter().for_each(|_| relative.push(".."));
+    path[common..].iter().for_each(|c| relative.push(c));
+    to_slash(&relative)
+}
+
+/// A path as used in links, always `/` separated
+pub fn to_slash(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+pub async fn head_sha(root: &Path) -> Result<String, GitCLIError> {
+    let sha = git(root, &["rev-parse", "HEAD"]).await?;
+    Ok(sha.trim().to_owned())
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    path.components().for_each(|component| match component {
+        Component::CurDir => {}
+        Component::ParentDir => {
+            normalized.pop();
+        }
+        component => normalized.push(component),
+    });
+    normalized
+}
+
+/// Clone `url` into `dir`, or fast forward it when it is already cloned
+pub async fn clone_or_pull(url: &str, dir: &Path) -> Result<(), GitCLIError> {
+    let credentials = [
+        "-c",
+        "credential.helper=",
+        "-c",
+        "credential.helper=!gh auth git-credential",
+    ];
+    if dir.join(".git").is_dir() {
+        git(
+            dir,
+            &[&credentials[..], &["pull", "--ff-only", "--quiet"]].concat(),
+        )
+        .await?;
+    } else {
+        let parent = dir.parent().ok_or(GitCLIError::NoRepo)?;
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|_| GitCLIError::NoRepo)?;
+        let dir = dir.to_string_lossy();
+        git(
+            parent,
+            &[&credentials[..], &["clone", "--quiet", url, &dir]].concat(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Author and relative date of the last commit touching `path`
+pub async fn last_edited(root: &Path, path: &Path) -> Result<String, GitCLIError> {
+    let path = path.to_string_lossy();
+    let last_edited = git(root, &["log", "-1", "--format=%an, %ar", "--", &path]).await?;
+    match last_edited.trim() {
+        "" => Err(GitCLIError::NoRef),
+        last_edited => Ok(last_edited.to_owned()),
+    }
+}
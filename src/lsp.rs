@@ -1,3 +1,4 @@
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 use tokio::time::timeout;
 use tower_lsp::jsonrpc::Result;
@@ -5,11 +6,29 @@ use tower_lsp::lsp_types::*;
 use tower_lsp::LanguageServer;
 
 use crate::backend::Backend;
-use crate::backend::TRIGGER_CHARACTERS;
+use crate::backend::{
+    COMMANDS, COPY_PERMALINK, CREATE_ISSUE, GENERATE_CHANGELOG, TRIGGER_CHARACTERS,
+};
+use crate::commit;
+use crate::gh::GetEdit;
+use crate::markdown;
+use crate::template;
+use crate::workflow;
+
+fn argument<T: DeserializeOwned>(arguments: &[Value], index: usize) -> Result<T> {
+    arguments
+        .get(index)
+        .cloned()
+        .and_then(|argument| serde_json::from_value(argument).ok())
+        .ok_or(tower_lsp::jsonrpc::Error::invalid_params(format!(
+            "Missing or invalid argument {index}"
+        )))
+}
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let _ = self.client_capabilities.set(params.capabilities);
         Ok(InitializeResult {
             server_info: None,
             capabilities: ServerCapabilities {
@@ -17,7 +36,7 @@ impl LanguageServer for Backend {
                     TextDocumentSyncKind::FULL,
                 )),
                 completion_provider: Some(CompletionOptions {
-                    resolve_provider: Some(false),
+                    resolve_provider: Some(true),
                     trigger_characters: Some(TRIGGER_CHARACTERS.map(String::from).to_vec()),
                     work_done_progress_options: Default::default(),
                     all_commit_characters: None,
@@ -32,6 +51,15 @@ impl LanguageServer for Backend {
                     file_operations: None,
                 }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: COMMANDS.map(String::from).to_vec(),
+                    work_done_progress_options: Default::default(),
+                }),
                 ..ServerCapabilities::default()
             },
         })
@@ -39,6 +67,7 @@ impl LanguageServer for Backend {
 
     async fn initialized(&self, _: InitializedParams) {
         self.initialize().await;
+        self.watch_wiki().await;
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -57,24 +86,108 @@ impl LanguageServer for Backend {
             .await;
     }
 
-    async fn did_change_watched_files(&self, _: DidChangeWatchedFilesParams) {
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
         self.client
             .log_message(MessageType::INFO, "watched files have changed!")
             .await;
+        let changed = params
+            .changes
+            .into_iter()
+            .map(|change| change.uri)
+            .collect::<Vec<Url>>();
+        self.reindex_wiki(&changed).await;
     }
 
-    async fn execute_command(&self, _: ExecuteCommandParams) -> Result<Option<Value>> {
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
         self.client
-            .log_message(MessageType::INFO, "command executed!")
+            .log_message(
+                MessageType::INFO,
+                format!("command executed: {}", params.command),
+            )
             .await;
 
-        match self.client.apply_edit(WorkspaceEdit::default()).await {
-            Ok(res) if res.applied => self.client.log_message(MessageType::INFO, "applied").await,
-            Ok(_) => self.client.log_message(MessageType::INFO, "rejected").await,
-            Err(err) => self.client.log_message(MessageType::ERROR, err).await,
+        match params.command.as_str() {
+            COPY_PERMALINK => {
+                let uri: Url = argument(&params.arguments, 0)?;
+                let range: Range = argument(&params.arguments, 1)?;
+                let permalink = self.permalink(&uri, range).await?;
+                self.client
+                    .show_message(MessageType::INFO, &permalink)
+                    .await;
+                Ok(Some(Value::String(permalink)))
+            }
+            CREATE_ISSUE => {
+                let uri: Url = argument(&params.arguments, 0)?;
+                let range: Range = argument(&params.arguments, 1)?;
+                // TODO comments are annotated with (#123) rather than replaced with a link
+                let annotate: Option<Position> = argument(&params.arguments, 2).ok();
+                let issue = self.create_issue(&uri, range).await?;
+                let text_edit = match annotate {
+                    Some(position) => TextEdit {
+                        range: Range {
+                            start: position,
+                            end: position,
+                        },
+                        new_text: format!("(#{})", issue.number),
+                    },
+                    None => TextEdit {
+                        range,
+                        new_text: issue.get_edit(),
+                    },
+                };
+                let edit = WorkspaceEdit {
+                    changes: Some([(uri, vec![text_edit])].into()),
+                    ..WorkspaceEdit::default()
+                };
+                match self.client.apply_edit(edit).await {
+                    Ok(res) if res.applied => {
+                        self.client.log_message(MessageType::INFO, "applied").await
+                    }
+                    Ok(_) => self.client.log_message(MessageType::INFO, "rejected").await,
+                    Err(err) => self.client.log_message(MessageType::ERROR, err).await,
+                }
+                Ok(Some(Value::String(issue.html_url.to_string())))
+            }
+            GENERATE_CHANGELOG => {
+                let uri: Url = argument(&params.arguments, 0)?;
+                let position: Position = argument(&params.arguments, 1)?;
+                let from: String = argument(&params.arguments, 2)?;
+                let to: String = argument(&params.arguments, 3).unwrap_or("HEAD".into());
+                let changelog = self.generate_changelog(&from, &to).await?;
+                let edit = WorkspaceEdit {
+                    changes: Some(
+                        [(
+                            uri,
+                            vec![TextEdit {
+                                range: Range {
+                                    start: position,
+                                    end: position,
+                                },
+                                new_text: changelog.to_owned(),
+                            }],
+                        )]
+                        .into(),
+                    ),
+                    ..WorkspaceEdit::default()
+                };
+                match self.client.apply_edit(edit).await {
+                    Ok(res) if res.applied => {
+                        self.client.log_message(MessageType::INFO, "applied").await
+                    }
+                    Ok(_) => self.client.log_message(MessageType::INFO, "rejected").await,
+                    Err(err) => self.client.log_message(MessageType::ERROR, err).await,
+                }
+                Ok(Some(Value::String(changelog)))
+            }
+            _ => Err(tower_lsp::jsonrpc::Error::method_not_found()),
         }
+    }
 
-        Ok(None)
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        Ok(Some(
+            self.on_code_action(&params.text_document.uri, params.range)
+                .await,
+        ))
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
@@ -85,7 +198,7 @@ impl LanguageServer for Backend {
             uri: params.text_document.uri,
             text: params.text_document.text,
             version: params.text_document.version,
-            language_id: "md".into(), //TODO: is this the way?
+            language_id: params.text_document.language_id,
         })
         .await
     }
@@ -100,11 +213,12 @@ impl LanguageServer for Backend {
             "\n".into()
         };
 
+        let language_id = self.language_of(&params.text_document.uri);
         self.on_change(TextDocumentItem {
             uri: params.text_document.uri,
             text,
             version: params.text_document.version,
-            language_id: "md".into(),
+            language_id,
         })
         .await;
     }
@@ -114,11 +228,12 @@ impl LanguageServer for Backend {
             .log_message(MessageType::INFO, "file saved!")
             .await;
         if let Some(text) = params.text {
+            let language_id = self.language_of(&params.text_document.uri);
             self.on_change(TextDocumentItem {
                 uri: params.text_document.uri,
                 text,
-                version: 0,               //TODO: not sure if we should forward version
-                language_id: "md".into(), //TODO: is this the way?
+                version: 0, //TODO: not sure if we should forward version
+                language_id,
             })
             .await
         }
@@ -157,15 +272,80 @@ impl LanguageServer for Backend {
         } else {
             word.split_at(1)
         };
+        let commit_message = self.is_commit_message(&uri);
+        let codeowners = self.is_codeowners(&uri);
+        let pull_request = self.is_pull_request(&uri);
+        let template = self
+            .is_issue_template(&uri)
+            .then(|| template::field_at(&rope.to_string(), position.line))
+            .flatten();
+        let uses = self
+            .is_workflow(&uri)
+            .then(|| workflow::uses(line))
+            .flatten();
+        // issues and repos are also searched remotely, so ask again as the needle grows
+        let is_incomplete = matches!(parts.0, "#" | "/")
+            && !commit_message
+            && !codeowners
+            && !pull_request
+            && template.is_none()
+            && uses.is_none()
+            && !word.contains("](")
+            && !word.contains("/blob/");
         let fast_ms = tokio::time::Duration::from_millis(200);
         let slow_ms = tokio::time::Duration::from_millis(3000);
-        let completions = match parts.0 {
-            "#" => timeout(fast_ms, self.search_issue_and_pr(position, parts.1)).await,
-            "@" => timeout(fast_ms, self.search_user(position, parts.1)).await,
-            "[" => timeout(fast_ms, self.search_wiki(position, parts.1)).await,
-            "/" => timeout(fast_ms, self.search_repo(position, parts.1)).await,
-            ":" => timeout(slow_ms, self.search_owner(position, parts.1)).await,
-            _ => Ok(Ok(vec![])),
+        let completions = if commit_message && line.starts_with('#') {
+            Ok(Ok(vec![])) // a comment git will strip
+        } else if commit_message && commit::is_trailer_start(line) {
+            // names of members not looked up yet take a moment, well within `slow_ms`
+            timeout(slow_ms, self.search_co_author(position, line)).await
+        } else if (commit_message || pull_request) && parts.0 == "#" {
+            // only open issues are worth closing
+            let previous = line[..line.len() - word.len()].split_whitespace().last();
+            let open_only = previous.is_some_and(commit::is_closing_keyword);
+            timeout(
+                fast_ms,
+                self.search_commit_issue(position, parts.1, open_only),
+            )
+            .await
+        } else if pull_request && parts.0 == "@" {
+            timeout(slow_ms, self.search_reviewer(position, parts.1)).await
+        } else if codeowners && line.trim_start().starts_with('#') {
+            Ok(Ok(vec![])) // a comment
+        } else if codeowners && parts.0 == "@" {
+            timeout(fast_ms, self.search_code_owner(position, parts.1)).await
+        } else if codeowners && line.trim_start() == word {
+            // the pattern comes first, owners after it
+            timeout(fast_ms, self.search_code_owner_path(position, &word)).await
+        } else if let Some((field, start)) = template {
+            let needle = template::needle(line, start);
+            timeout(fast_ms, self.search_template_value(position, field, needle)).await
+        } else if let Some((_, action)) = uses {
+            timeout(slow_ms, self.search_action(position, action)).await
+        } else if let Some((repo_link, blob)) = word.split_once("/blob/") {
+            timeout(slow_ms, self.search_blob_path(position, repo_link, blob)).await
+        } else if let Some((_, target)) = word.rsplit_once("](") {
+            timeout(fast_ms, self.search_path(&uri, position, target)).await
+        } else {
+            match parts.0 {
+                "#" => timeout(slow_ms, self.search_issue_and_pr_live(position, parts.1)).await,
+                "@" => timeout(fast_ms, self.search_user(position, parts.1)).await,
+                "[" => timeout(fast_ms, self.search_wiki(&uri, position, parts.1)).await,
+                "/" => timeout(slow_ms, self.search_repo_live(position, parts.1)).await,
+                // `::` always searches owners, `:` prefers emoji shortcodes and only falls back to
+                // the (slow) owner search when no shortcode matches, not while emojis are loading
+                ":" if parts.1.starts_with(':') => {
+                    timeout(slow_ms, self.search_owner(position, parts.1)).await
+                }
+                ":" if parts.1.ends_with(':') => Ok(Ok(vec![])), // a finished shortcode
+                ":" => match self.search_emoji(position, parts.1).await {
+                    Ok(emojis) if emojis.is_empty() && !self.emoji_map.is_empty() => {
+                        timeout(slow_ms, self.search_owner(position, parts.1)).await
+                    }
+                    emojis => Ok(emojis),
+                },
+                _ => Ok(Ok(vec![])),
+            }
         };
 
         let completions = if let Ok(completions) = completions {
@@ -173,7 +353,20 @@ impl LanguageServer for Backend {
         } else {
             Some(vec![])
         };
-        Ok(completions.map(CompletionResponse::Array))
+        Ok(completions.map(|items| {
+            if is_incomplete {
+                CompletionResponse::List(CompletionList {
+                    is_incomplete,
+                    items,
+                })
+            } else {
+                CompletionResponse::Array(items)
+            }
+        }))
+    }
+
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        Ok(self.on_completion_resolve(item).await)
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -189,57 +382,94 @@ impl LanguageServer for Backend {
             .ok_or(tower_lsp::jsonrpc::Error::internal_error())?;
         let character_pos = position.character as usize;
 
-        //TODO: cleanup parsing; possible to clean up with treesitter? need to investigate
         let line = line.to_string();
-        let line = line.trim();
-        // scan backwards
-        let mut start = character_pos;
-        let mut start_search = start;
-        start = usize::MAX;
-        loop {
-            // look for the start of the (..) link part
-            if line.as_bytes()[start_search] == b'(' {
-                start = start_search + 1; // skip (
-                break;
+        drop(rope);
+        if self.is_workflow(&uri) {
+            if let Some((start, uses)) = workflow::uses(&line) {
+                let start = line[..start].chars().count();
+                let end = start + uses.chars().count();
+                if (start..=end).contains(&character_pos) && !uses.is_empty() {
+                    return Ok(self.on_action_hover(uses).await);
+                }
             }
-            if start_search == 0 {
-                break;
-            }
-            start_search -= 1;
         }
-        // scan forwards
-        let mut end = character_pos;
-        let mut end_search = end;
-        end = usize::MIN;
-        loop {
-            // handle hover over the [..] part of a link
-            if start == usize::MAX && line.as_bytes()[end_search] == b'(' {
-                start = end_search + 1;
-            }
-            if line.as_bytes()[end_search] == b')' {
-                end = end_search; // str[..] slice will exclude ), non inclusive
-                break;
-            }
-            if end_search == line.len() - 1 {
-                break;
-            }
-            end_search += 1;
+        // `(#38)` and `(see @user)` look like the `(..)` of a link too, so references go first
+        if let Some(hover) = self.on_reference_hover(&uri, position).await {
+            return Ok(Some(hover));
         }
-
-        if start == usize::MAX || end == usize::MIN || start >= end {
+        let Some(link) = markdown::link_at(&line, character_pos) else {
             self.client
                 .log_message(
                     MessageType::ERROR,
-                    format!(
-                        "Hover search failed with invalid start {} end {} for line {}",
-                        start, end, line
-                    ),
+                    format!("Hover search failed at {} for line {}", character_pos, line),
                 )
                 .await;
             return Ok(None);
-        }
-        let link: String = line[start..end].into();
+        };
 
         self.on_hover(link).await
     }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let rope = self
+            .document_map
+            .get(&uri.to_string())
+            .ok_or(tower_lsp::jsonrpc::Error::invalid_request())?;
+
+        let line = rope
+            .get_line(position.line as usize)
+            .ok_or(tower_lsp::jsonrpc::Error::internal_error())?;
+        let Some(link) = markdown::link_at(&line.to_string(), position.character as usize) else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .on_definition(&uri, link)
+            .await
+            .map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let rope = self
+            .document_map
+            .get(&uri.to_string())
+            .ok_or(tower_lsp::jsonrpc::Error::invalid_request())?;
+
+        let line = rope
+            .get_line(position.line as usize)
+            .ok_or(tower_lsp::jsonrpc::Error::internal_error())?
+            .to_string();
+        // we read other documents while renaming
+        drop(rope);
+        // [[Page]] links, or links to the wiki on GitHub
+        let page = markdown::wiki_link_at(&line, position.character).or_else(|| {
+            markdown::link_at(&line, position.character as usize)
+                .and_then(|link| self.wiki_page(&link))
+        });
+        let Some(page) = page else {
+            return Ok(None);
+        };
+
+        self.on_rename_wiki(&page, &params.new_name).await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        Ok(self.on_references(&uri, position).await)
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        Ok(Some(self.on_workspace_symbol(&params.query).await))
+    }
 }
@@ -3,23 +3,38 @@ use octocrab::Octocrab;
 use tower_lsp::{LspService, Server};
 
 use crate::gh::{gh_cli_owner_name, gh_token};
+use crate::git::git_toplevel;
 
 mod backend;
+mod changelog;
+mod codeowners;
+mod commit;
 mod gh;
+mod git;
 mod lsp;
+mod markdown;
+mod pull_request;
+mod reference;
+mod template;
+mod todo;
+mod workflow;
 
 #[tokio::main]
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let token = gh_token().await?;
     let octocrab = Octocrab::builder().personal_token(token.clone()).build()?;
     let owner_repo = gh_cli_owner_name().await?;
+    let root = match git_toplevel().await {
+        Ok(root) => root,
+        Err(_) => std::env::current_dir()?,
+    };
 
     tracing_subscriber::fmt().init();
 
     let (stdin, stdout) = (tokio::io::stdin(), tokio::io::stdout());
 
     let (service, socket) =
-        LspService::new(|client| Backend::new(client, octocrab, owner_repo.0, owner_repo.1));
+        LspService::new(|client| Backend::new(client, octocrab, owner_repo.0, owner_repo.1, root));
     Server::new(stdin, stdout, socket).serve(service).await;
     Ok(())
 }
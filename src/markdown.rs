@@ -0,0 +1,302 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Heading {
+    pub title: String,
+    pub slug: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Link {
+    pub target: String,
+    pub range: Range,
+}
+
+/// The anchor GitHub generates for a heading, without the `-1` suffix of duplicates
+pub fn slugify(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|ch| ch.is_alphanumeric() || *ch == ' ' || *ch == '-' || *ch == '_')
+        .map(|ch| if ch == ' ' { '-' } else { ch })
+        .collect()
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// ATX headings (`# Title`) outside of fenced code blocks
+pub fn headings(text: &str) -> Vec<Heading> {
+    let mut in_fence = false;
+    let mut headings: Vec<Heading> = vec![];
+    for (line_number, line) in text.lines().enumerate() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let level = trimmed.chars().take_while(|ch| *ch == '#').count();
+        if level == 0 || level > 6 || !trimmed[level..].starts_with(' ') {
+            continue;
+        }
+        let title = trimmed[level..]
+            .trim()
+            .trim_end_matches('#')
+            .trim()
+            .to_owned();
+        let slug = slugify(&title);
+        let duplicates = headings
+            .iter()
+            .filter(|heading| slugify(&heading.title) == slug)
+            .count();
+        let slug = if duplicates == 0 {
+            slug
+        } else {
+            format!("{slug}-{duplicates}")
+        };
+        headings.push(Heading {
+            title,
+            slug,
+            line: line_number as u32,
+        });
+    }
+    headings
+}
+
+/// The first paragraph of prose, skipping headings, code blocks and front matter
+pub fn first_paragraph(text: &str) -> String {
+    let mut in_fence = false;
+    let mut paragraph: Vec<&str> = vec![];
+    for line in text.lines() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        let trimmed = line.trim();
+        if in_fence || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+        if trimmed.is_empty() {
+            if paragraph.is_empty() {
+                continue;
+            }
+            break;
+        }
+        paragraph.push(trimmed);
+    }
+    paragraph.join("\n")
+}
+
+/// Targets of inline `[text](target)` links and images outside of fenced code blocks
+pub fn links(text: &str) -> Vec<Link> {
+    let mut in_fence = false;
+    let mut links: Vec<Link> = vec![];
+    for (line_number, line) in text.lines().enumerate() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut rest = line;
+        let mut offset = 0;
+        while let Some(open) = rest.find("](") {
+            let start = open + 2;
+            let Some(close) = rest[start..].find(')') else {
+                break;
+            };
+            // drop an optional "title" and <> around the destination, the range covers the
+            // target only so that replacing it keeps the title
+            let inner = &rest[start..start + close];
+            let destination = inner.trim_start();
+            let destination = destination.split_whitespace().next().unwrap_or(destination);
+            let target = destination.trim_start_matches('<');
+            let leading = inner.len() - inner.trim_start().len() + destination.len() - target.len();
+            let target = target.trim_end_matches('>');
+            let target_start = offset + start + leading;
+            let character = |index: usize| line[..index].chars().count() as u32;
+            links.push(Link {
+                target: target.to_owned(),
+                range: Range {
+                    start: Position {
+                        line: line_number as u32,
+                        character: character(target_start),
+                    },
+                    end: Position {
+                        line: line_number as u32,
+                        character: character(target_start + target.len()),
+                    },
+                },
+            });
+            offset += start + close;
+            rest = &line[offset..];
+        }
+    }
+    links
+}
+
+/// The `(..)` part of the link under the cursor, also when hovering the `[..]` part
+pub fn link_at(line: &str, character: usize) -> Option<String> {
+    //TODO: cleanup parsing; possible to clean up with treesitter? need to investigate
+    let line = line.trim_end();
+    let bytes = line.as_bytes();
+    if bytes.is_empty() {
+        return None;
+    }
+    let character = character.min(bytes.len() - 1);
+    // scan backwards, look for the start of the (..) link part
+    let mut start = bytes[..=character]
+        .iter()
+        .rposition(|byte| *byte == b'(')
+        // skip (
+        .map(|open| open + 1);
+    // scan forwards
+    let mut end = None;
+    for (index, byte) in bytes.iter().enumerate().skip(character) {
+        // handle hover over the [..] part of a link
+        if start.is_none() && *byte == b'(' {
+            start = Some(index + 1);
+        }
+        if *byte == b')' {
+            end = Some(index); // str[..] slice will exclude ), non inclusive
+            break;
+        }
+    }
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => line.get(start..end).map(String::from),
+        _ => None,
+    }
+}
+
+/// Page names of wiki `[[Page Name]]` and `[[Text|Page Name]]` links outside of fenced code blocks
+pub fn wiki_links(text: &str) -> Vec<Link> {
+    let mut in_fence = false;
+    let mut links: Vec<Link> = vec![];
+    for (line_number, line) in text.lines().enumerate() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut offset = 0;
+        while let Some(open) = line[offset..].find("[[") {
+            let start = offset + open + 2;
+            let Some(close) = line[start..].find("]]") else {
+                break;
+            };
+            let inner = &line[start..start + close];
+            // the page is after the |, the text before it
+            let (start, page) = match inner.rsplit_once('|') {
+                Some((text, page)) => (start + text.len() + 1, page),
+                None => (start, inner),
+            };
+            let character = line[..start].chars().count() as u32;
+            links.push(Link {
+                target: page.to_owned(),
+                range: Range {
+                    start: Position {
+                        line: line_number as u32,
+                        character,
+                    },
+                    end: Position {
+                        line: line_number as u32,
+                        character: character + page.chars().count() as u32,
+                    },
+                },
+            });
+            offset = start + page.len() + 2;
+        }
+    }
+    links
+}
+
+/// The page of the `[[..]]` wiki link under the cursor
+pub fn wiki_link_at(line: &str, character: u32) -> Option<String> {
+    wiki_links(line)
+        .into_iter()
+        .find(|link| {
+            link.range.start.character <= character && character <= link.range.end.character
+        })
+        .map(|link| link.target)
+}
+
+/// Links to other files in the repository, as opposed to URLs or e-mail addresses
+pub fn is_relative(target: &str) -> bool {
+    !(target.is_empty() || target.contains("://") || target.starts_with("mailto:"))
+}
+
+pub fn is_markdown(path: &str) -> bool {
+    path.ends_with(".md") || path.ends_with(".markdown")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(links: Vec<Link>) -> Vec<(String, u32, u32)> {
+        links
+            .into_iter()
+            .map(|link| {
+                (
+                    link.target,
+                    link.range.start.character,
+                    link.range.end.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn links_cover_the_target_only() {
+        assert_eq!(
+            ranges(links(r#"[a](b.md) and [c](<d.md> "title")"#)),
+            [("b.md".into(), 4, 8), ("d.md".into(), 19, 23)]
+        );
+        assert_eq!(ranges(links("[é](x.md#top)")), [("x.md#top".into(), 4, 12)]);
+        assert!(links("```\n[a](b.md)\n```").is_empty());
+    }
+
+    #[test]
+    fn links_with_empty_targets() {
+        assert_eq!(ranges(links("[x]()")), [(String::new(), 4, 4)]);
+        assert_eq!(ranges(links("[x]( )")), [(String::new(), 5, 5)]);
+    }
+
+    #[test]
+    fn wiki_links_cover_the_page_only() {
+        assert_eq!(
+            ranges(wiki_links("see [[Home]] and [[Text|Other Page]]")),
+            [("Home".into(), 6, 10), ("Other Page".into(), 24, 34)]
+        );
+        assert_eq!(wiki_link_at("[[é|Page]]", 5), Some("Page".into()));
+        assert!(wiki_links("[[unclosed").is_empty());
+    }
+
+    #[test]
+    fn slugs_number_duplicate_headings() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("snake_case and-dash"), "snake_case-and-dash");
+        let slugs = headings("# Intro\n## Intro\n```\n# Intro\n```\n# Intro #\n#hashtag")
+            .into_iter()
+            .map(|heading| (heading.slug, heading.line))
+            .collect::<Vec<(String, u32)>>();
+        assert_eq!(
+            slugs,
+            [
+                ("intro".into(), 0),
+                ("intro-1".into(), 1),
+                ("intro-2".into(), 5)
+            ]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use crate::markdown;
+
+/// Where GitHub looks for the pull request template, in order
+pub const TEMPLATES: [&str; 4] = [
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "docs/pull_request_template.md",
+];
+
+/// `PULLREQ_EDITMSG`, or the Markdown file `gh pr create` opens in the editor from the temp dir
+pub fn is_pull_request(path: &Path) -> bool {
+    if path
+        .file_name()
+        .is_some_and(|name| name == "PULLREQ_EDITMSG")
+    {
+        return true;
+    }
+    path.parent() == Some(std::env::temp_dir().as_path())
+        && markdown::is_markdown(&path.to_string_lossy())
+}
@@ -0,0 +1,386 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+/// Something on GitHub a document refers to, with or without a full link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// Issues and PRs share their numbers
+    Issue {
+        owner: String,
+        repo: String,
+        number: u64,
+    },
+    /// `@login`, team mentions are left alone
+    User { login: String },
+    /// Abbreviated or full SHAs
+    Commit {
+        owner: String,
+        repo: String,
+        sha: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Reference {
+    pub target: Target,
+    pub range: Range,
+}
+
+impl Reference {
+    pub fn contains(&self, position: Position) -> bool {
+        self.range.start <= position && position <= self.range.end
+    }
+}
+
+fn is_word(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-' || ch == '.' || ch == '/' || ch == '&'
+}
+
+fn digits(text: &str) -> Option<(u64, usize)> {
+    let len = text.chars().take_while(char::is_ascii_digit).count();
+    text[..len].parse().ok().map(|number| (number, len))
+}
+
+fn range(line: &str, line_number: usize, start: usize, end: usize) -> Range {
+    let character = |index: usize| line[..index].chars().count() as u32;
+    Range {
+        start: Position {
+            line: line_number as u32,
+            character: character(start),
+        },
+        end: Position {
+            line: line_number as u32,
+            character: character(end),
+        },
+    }
+}
+
+/// `https://github.com/<owner>/<repo>/issues/<number>`, also for pulls
+fn issue_links(line: &str, line_number: usize) -> Vec<Reference> {
+    let mut references: Vec<Reference> = vec![];
+    let prefix = "https://github.com/";
+    let mut offset = 0;
+    while let Some(found) = line[offset..].find(prefix) {
+        let start = offset + found;
+        offset = start + prefix.len();
+        let mut parts = line[offset..].splitn(4, '/');
+        let (Some(owner), Some(repo), Some(kind), Some(rest)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if kind != "issues" && kind != "pull" {
+            continue;
+        }
+        let Some((number, len)) = digits(rest) else {
+            continue;
+        };
+        let end = offset + owner.len() + repo.len() + kind.len() + 3 + len;
+        references.push(Reference {
+            target: Target::Issue {
+                owner: owner.to_owned(),
+                repo: repo.to_owned(),
+                number,
+            },
+            range: range(line, line_number, start, end),
+        });
+        offset = end;
+    }
+    references
+}
+
+/// `#123`, `GH-123` and `owner/repo#123`, the way GitHub autolinks them
+fn issue_autolinks(line: &str, line_number: usize, owner: &str, repo: &str) -> Vec<Reference> {
+    let mut references: Vec<Reference> = vec![];
+    for (index, _) in line.match_indices('#').chain(line.match_indices("GH-")) {
+        let marker = if line[index..].starts_with('#') { 1 } else { 3 };
+        let Some((number, len)) = digits(&line[index + marker..]) else {
+            continue;
+        };
+        let end = index + marker + len;
+        if line[end..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let before = &line[..index];
+        let word_start = before
+            .char_indices()
+            .rev()
+            .take_while(|(_, ch)| is_word(*ch))
+            .last()
+            .map(|(start, _)| start)
+            .unwrap_or(index);
+        let word = &before[word_start..];
+        let target = match word.split_once('/') {
+            None if word.is_empty() => Target::Issue {
+                owner: owner.to_owned(),
+                repo: repo.to_owned(),
+                number,
+            },
+            Some((owner, repo)) if marker == 1 && !owner.is_empty() && !repo.contains('/') => {
+                Target::Issue {
+                    owner: owner.to_owned(),
+                    repo: repo.to_owned(),
+                    number,
+                }
+            }
+            _ => continue,
+        };
+        references.push(Reference {
+            target,
+            range: range(line, line_number, word_start, end),
+        });
+    }
+    references
+}
+
+/// `@login` mentions, but not e-mail addresses or `@org/team` mentions
+fn user_mentions(line: &str, line_number: usize) -> Vec<Reference> {
+    let mut references: Vec<Reference> = vec![];
+    for (index, _) in line.match_indices('@') {
+        if line[..index]
+            .chars()
+            .next_back()
+            .is_some_and(|ch| is_word(ch) || ch == '@' || ch == '`')
+        {
+            continue;
+        }
+        let rest = &line[index + 1..];
+        let len = rest
+            .chars()
+            .take_while(|ch| ch.is_ascii_alphanumeric() || *ch == '-')
+            .count();
+        let login = &rest[..len];
+        if login.is_empty() || login.starts_with('-') || rest[len..].starts_with('/') {
+            continue;
+        }
+        references.push(Reference {
+            target: Target::User {
+                login: login.to_owned(),
+            },
+            range: range(line, line_number, index, index + 1 + len),
+        });
+    }
+    references
+}
+
+/// Bare SHAs of 7 to 40 characters and `owner/repo@sha`, which GitHub links to the commit
+fn commits(line: &str, line_number: usize, owner: &str, repo: &str) -> Vec<Reference> {
+    let mut references: Vec<Reference> = vec![];
+    let mut offset = 0;
+    while offset < line.len() {
+        let rest = &line[offset..];
+        let Some(skip) = rest.find(|ch: char| ch.is_ascii_alphanumeric()) else {
+            break;
+        };
+        let start = offset + skip;
+        let len = line[start..]
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .count();
+        let end = start + len;
+        offset = end;
+        let sha = &line[start..end];
+        // words like `deadbeef` or numbers like `2024100` are not worth a lookup
+        if !(7..=40).contains(&len)
+            || !sha.chars().all(|ch| matches!(ch, '0'..='9' | 'a'..='f'))
+            || !sha.chars().any(|ch| ch.is_ascii_digit())
+            || !sha.chars().any(|ch| ch.is_ascii_alphabetic())
+            || line[end..].starts_with(['-', '_', '/'])
+        {
+            continue;
+        }
+        let before = &line[..start];
+        let (target, start) = match before.chars().next_back() {
+            Some('@') => {
+                let at = start - 1;
+                let word_start = before[..at]
+                    .char_indices()
+                    .rev()
+                    .take_while(|(_, ch)| is_word(*ch))
+                    .last()
+                    .map(|(start, _)| start)
+                    .unwrap_or(at);
+                let Some((owner, repo)) = before[word_start..at].split_once('/') else {
+                    continue;
+                };
+                if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+                    continue;
+                }
+                let target = Target::Commit {
+                    owner: owner.to_owned(),
+                    repo: repo.to_owned(),
+                    sha: sha.to_owned(),
+                };
+                (target, word_start)
+            }
+            Some(ch) if is_word(ch) || ch == '#' => continue,
+            _ => {
+                let target = Target::Commit {
+                    owner: owner.to_owned(),
+                    repo: repo.to_owned(),
+                    sha: sha.to_owned(),
+                };
+                (target, start)
+            }
+        };
+        references.push(Reference {
+            target,
+            range: range(line, line_number, start, end),
+        });
+    }
+    references
+}
+
+/// Where the comment of a line of code starts, for `//`, `/* */`, `#`, `--` and `<!-- -->`
+/// comments, but not the `//` of a URL
+fn comment_start(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    // the inside of a block comment
+    if trimmed.starts_with('*') {
+        return Some(line.len() - trimmed.len());
+    }
+    ["//", "/*", "<!--", "-- ", "# "]
+        .iter()
+        .flat_map(|marker| line.match_indices(marker))
+        .filter(|(index, marker)| *marker != "//" || !line[..*index].ends_with(':'))
+        .map(|(index, _)| index)
+        .chain((trimmed == "#" || trimmed == "--").then_some(line.len() - trimmed.len()))
+        .min()
+}
+
+/// Every reference in a document of any kind, owner and repo are used for the short forms.
+/// Prose is searched as a whole. In code full links count anywhere, but the short forms only in
+/// comments, elsewhere `@Override` or `color: #333` would look like them, and SHAs not at all, any
+/// hex string would look like one.
+pub fn references(text: &str, owner: &str, repo: &str, prose: bool) -> Vec<Reference> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(line_number, line)| {
+            let mut references = issue_links(line, line_number);
+            let comment = if prose { Some(0) } else { comment_start(line) };
+            let Some(comment) = comment else {
+                return references;
+            };
+            let comment = line[..comment].chars().count() as u32;
+            let mut short_forms = issue_autolinks(line, line_number, owner, repo);
+            short_forms.append(&mut user_mentions(line, line_number));
+            if prose {
+                short_forms.append(&mut commits(line, line_number, owner, repo));
+            }
+            references.extend(
+                short_forms
+                    .into_iter()
+                    .filter(|reference| reference.range.start.character >= comment),
+            );
+            references
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str, prose: bool) -> Vec<(Target, u32, u32)> {
+        references(text, "owner", "repo", prose)
+            .into_iter()
+            .map(|reference| {
+                (
+                    reference.target,
+                    reference.range.start.character,
+                    reference.range.end.character,
+                )
+            })
+            .collect()
+    }
+
+    fn issue(owner: &str, repo: &str, number: u64) -> Target {
+        Target::Issue {
+            owner: owner.into(),
+            repo: repo.into(),
+            number,
+        }
+    }
+
+    fn user(login: &str) -> Target {
+        Target::User {
+            login: login.into(),
+        }
+    }
+
+    fn commit(owner: &str, repo: &str, sha: &str) -> Target {
+        Target::Commit {
+            owner: owner.into(),
+            repo: repo.into(),
+            sha: sha.into(),
+        }
+    }
+
+    #[test]
+    fn finds_issue_autolinks() {
+        assert_eq!(
+            found("see #12 and GH-3", true),
+            [
+                (issue("owner", "repo", 12), 4, 7),
+                (issue("owner", "repo", 3), 12, 16)
+            ]
+        );
+        assert_eq!(
+            found("fixed in octo/cat#7", true),
+            [(issue("octo", "cat", 7), 9, 19)]
+        );
+        assert_eq!(
+            found("https://github.com/octo/cat/pull/8", true),
+            [(issue("octo", "cat", 8), 0, 34)]
+        );
+        assert!(found("a#12 #12abc #", true).is_empty());
+    }
+
+    #[test]
+    fn finds_user_mentions() {
+        assert_eq!(found("thanks @octocat!", true), [(user("octocat"), 7, 15)]);
+        assert!(found("mail me@example.com", true).is_empty());
+        assert!(found("ping @org/team", true).is_empty());
+        assert!(found("`@octocat` and @@octocat", true).is_empty());
+    }
+
+    #[test]
+    fn finds_short_forms_only_in_the_comments_of_code() {
+        assert!(found("@Override", false).is_empty());
+        assert!(found("    @param name", false).is_empty());
+        assert!(found("color: #333;", false).is_empty());
+        assert_eq!(
+            found("x = 1 // see #12 @octocat", false),
+            [
+                (issue("owner", "repo", 12), 13, 16),
+                (user("octocat"), 17, 25)
+            ]
+        );
+        assert_eq!(
+            found("# fixes #5", false),
+            [(issue("owner", "repo", 5), 8, 10)]
+        );
+        assert_eq!(found(" * @octocat", false), [(user("octocat"), 3, 11)]);
+        assert_eq!(
+            found(r#"let url = "https://github.com/o/r/issues/5";"#, false),
+            [(issue("o", "r", 5), 11, 42)]
+        );
+    }
+
+    #[test]
+    fn finds_shas_only_in_prose() {
+        assert_eq!(
+            found("fixed in 1a2b3c4d", true),
+            [(commit("owner", "repo", "1a2b3c4d"), 9, 17)]
+        );
+        assert_eq!(
+            found("octo/cat@1a2b3c4", true),
+            [(commit("octo", "cat", "1a2b3c4"), 0, 16)]
+        );
+        assert!(found("deadbeef 2024100 1a2b3c 1a2b3c4-x", true).is_empty());
+        assert!(found("// fixed in 1a2b3c4d", false).is_empty());
+    }
+}
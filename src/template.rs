@@ -0,0 +1,188 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+/// The keys of an issue template that must name things that exist on GitHub
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Field {
+    Labels,
+    Assignees,
+}
+
+/// A single label or assignee of an issue template
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub field: Field,
+    pub value: String,
+    pub range: Range,
+}
+
+/// Markdown templates and YAML issue forms, but not the `config.yml` of the template chooser
+pub fn is_issue_template(path: &str) -> bool {
+    let Some((_, file)) = path.rsplit_once(".github/ISSUE_TEMPLATE/") else {
+        return false;
+    };
+    !file.contains('/')
+        && !file.starts_with("config.")
+        && [".md", ".yml", ".yaml"]
+            .iter()
+            .any(|extension| file.ends_with(extension))
+}
+
+/// For every line of the YAML, the field it holds values of and where they start in the line.
+/// That is all of a form, and only the front matter of a Markdown template.
+fn fields(text: &str) -> Vec<Option<(Field, usize)>> {
+    let mut field = None;
+    let mut front_matter = false;
+    let mut done = false;
+    text.lines()
+        .enumerate()
+        .map(|(line_number, line)| {
+            if line.trim_end() == "---" {
+                done = done || front_matter;
+                front_matter = line_number == 0;
+                return None;
+            }
+            if done {
+                return None;
+            }
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') || trimmed.is_empty() {
+                None
+            } else if trimmed.starts_with('-') {
+                // an item of a block sequence under the last key
+                field.map(|field| (field, line.len() - trimmed.len() + 1))
+            } else if trimmed.len() == line.len() {
+                let (key, _) = line.split_once(':')?;
+                field = match key.trim() {
+                    "labels" => Some(Field::Labels),
+                    "assignees" => Some(Field::Assignees),
+                    _ => None,
+                };
+                field.map(|field| (field, key.len() + 1))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The field the values on `line` belong to, and where they start in that line
+pub fn field_at(text: &str, line: u32) -> Option<(Field, usize)> {
+    fields(text).get(line as usize).copied().flatten()
+}
+
+/// The value being typed at the end of `line`, with `start` from `field_at`
+pub fn needle(line: &str, start: usize) -> &str {
+    line.get(start..)
+        .unwrap_or_default()
+        .rsplit(['[', ']', ','])
+        .next()
+        .unwrap_or_default()
+        .trim_start()
+        .trim_start_matches(['"', '\''])
+}
+
+/// `labels: bug`, `labels: bug, triage`, `labels: ["bug", "triage"]` and block sequences
+pub fn entries(text: &str) -> Vec<Entry> {
+    text.lines()
+        .zip(fields(text))
+        .enumerate()
+        .flat_map(|(line_number, (line, field))| {
+            let Some((field, start)) = field else {
+                return vec![];
+            };
+            let end = line
+                .find(" #")
+                .filter(|end| *end >= start)
+                .unwrap_or(line.len());
+            let character = |index: usize| line[..index].chars().count() as u32;
+            line[start..end]
+                .split(['[', ']', ','])
+                .map(|segment| segment.trim().trim_matches(['"', '\'']))
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    let start = value.as_ptr() as usize - line.as_ptr() as usize;
+                    Entry {
+                        field,
+                        value: value.to_owned(),
+                        range: Range {
+                            start: Position {
+                                line: line_number as u32,
+                                character: character(start),
+                            },
+                            end: Position {
+                                line: line_number as u32,
+                                character: character(start + value.len()),
+                            },
+                        },
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<(Field, String, u32, u32, u32)> {
+        entries(text)
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.field,
+                    entry.value,
+                    entry.range.start.line,
+                    entry.range.start.character,
+                    entry.range.end.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_issue_templates() {
+        assert!(is_issue_template("repo/.github/ISSUE_TEMPLATE/bug.yml"));
+        assert!(is_issue_template("repo/.github/ISSUE_TEMPLATE/feature.md"));
+        assert!(!is_issue_template("repo/.github/ISSUE_TEMPLATE/config.yml"));
+        assert!(!is_issue_template(
+            "repo/.github/ISSUE_TEMPLATE/old/bug.yml"
+        ));
+    }
+
+    #[test]
+    fn entries_of_flow_and_block_sequences() {
+        assert_eq!(
+            values("labels: bug, \"triage\" # why\nassignees: [octocat]\n"),
+            [
+                (Field::Labels, "bug".into(), 0, 8, 11),
+                (Field::Labels, "triage".into(), 0, 14, 20),
+                (Field::Assignees, "octocat".into(), 1, 12, 19),
+            ]
+        );
+        assert_eq!(
+            values("labels:\n  - bug\n  - é\nname: x\n  - no\n"),
+            [
+                (Field::Labels, "bug".into(), 1, 4, 7),
+                (Field::Labels, "é".into(), 2, 4, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_templates_only_use_their_front_matter() {
+        assert_eq!(
+            values("---\nlabels: bug\n---\nlabels: nope\n"),
+            [(Field::Labels, "bug".into(), 1, 8, 11)]
+        );
+    }
+
+    #[test]
+    fn needle_is_the_value_being_typed() {
+        let line = "labels: [bug, 'tri";
+        let (field, start) = field_at(line, 0).unwrap();
+        assert_eq!(field, Field::Labels);
+        assert_eq!(needle(line, start), "tri");
+        assert_eq!(field_at("name: x", 0), None);
+    }
+}
@@ -0,0 +1,125 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Todo {
+    /// the `123` of `TODO(#123)` when the TODO is tracked by an issue
+    pub issue: Option<u64>,
+    /// right after `TODO`, where `(#123)` goes
+    pub marker_end: Position,
+    /// what is left to do, without the `TODO:` marker
+    pub range: Range,
+}
+
+const MARKERS: [&str; 2] = ["TODO", "FIXME"];
+
+/// Where a marker starts, as a whole word followed by `:` or `(#123)`, so that identifiers like
+/// `TODOS` or strings like `"TODO"` are left alone
+fn marker_at(line: &str) -> Option<(usize, &str)> {
+    MARKERS
+        .iter()
+        .flat_map(|marker| line.match_indices(marker))
+        .filter(|(index, marker)| {
+            let before = line[..*index].chars().next_back();
+            let after = &line[index + marker.len()..];
+            !before.is_some_and(|ch| ch.is_alphanumeric() || ch == '_')
+                && (after.starts_with(':') || after.starts_with("(#"))
+        })
+        .min()
+}
+
+/// `TODO: ...`, `TODO(#123): ...` and the same for `FIXME`, in any file type
+pub fn todos(text: &str) -> Vec<Todo> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line_number, line)| {
+            let (index, marker) = marker_at(line)?;
+            let marker_end = index + marker.len();
+            let rest = &line[marker_end..];
+            let issue = rest
+                .strip_prefix("(#")
+                .and_then(|rest| rest.split_once(')'))
+                .and_then(|(number, rest)| Some((number.parse::<u64>().ok()?, rest)));
+            if issue.is_none() && !rest.starts_with(':') {
+                return None;
+            }
+            let rest = issue.map(|(_, rest)| rest).unwrap_or(rest);
+            let rest = rest.strip_prefix(':').unwrap_or(rest);
+            let start = line.len() - rest.len() + (rest.len() - rest.trim_start().len());
+            // leave the end of block comments alone
+            let text = line[start..]
+                .trim_end()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim_end();
+            if text.is_empty() && issue.is_none() {
+                return None;
+            }
+            let character = |index: usize| line[..index].chars().count() as u32;
+            Some(Todo {
+                issue: issue.map(|(number, _)| number),
+                marker_end: Position {
+                    line: line_number as u32,
+                    character: character(marker_end),
+                },
+                range: Range {
+                    start: Position {
+                        line: line_number as u32,
+                        character: character(start),
+                    },
+                    end: Position {
+                        line: line_number as u32,
+                        character: character(start + text.len()),
+                    },
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<(Option<u64>, String)> {
+        todos(text)
+            .into_iter()
+            .map(|todo| {
+                let line = text.lines().nth(todo.range.start.line as usize).unwrap();
+                let chars = line.chars().collect::<Vec<char>>();
+                let range = todo.range.start.character as usize..todo.range.end.character as usize;
+                (todo.issue, chars[range].iter().collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_todos_and_fixmes() {
+        assert_eq!(texts("// TODO: cache this"), [(None, "cache this".into())]);
+        assert_eq!(texts("# FIXME: flaky"), [(None, "flaky".into())]);
+        assert_eq!(
+            texts("/* TODO(#12): track it */"),
+            [(Some(12), "track it".into())]
+        );
+        assert_eq!(texts("//TODO(#12)"), [(Some(12), String::new())]);
+        assert_eq!(
+            texts("<!-- TODO: write docs -->"),
+            [(None, "write docs".into())]
+        );
+    }
+
+    #[test]
+    fn marker_end_is_right_after_the_marker() {
+        let todo = &todos("  // TODO: x")[0];
+        assert_eq!(todo.marker_end, Position::new(0, 9));
+    }
+
+    #[test]
+    fn skips_markers_that_are_not_comments() {
+        assert!(todos(r#"const MARKERS: [&str; 2] = ["TODO", "FIXME"];"#).is_empty());
+        assert!(todos("let list = TODOS_LIST;").is_empty());
+        assert!(todos("let MY_TODO: u8 = 1;").is_empty());
+        assert!(todos("// TODO").is_empty());
+        assert!(todos("// TODO:").is_empty());
+        assert!(todos("// TODO later").is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+pub fn is_workflow(path: &str) -> bool {
+    (path.contains("/.github/workflows/") || path.starts_with(".github/workflows/"))
+        && (path.ends_with(".yml") || path.ends_with(".yaml"))
+}
+
+/// The value of a `uses:` step or job key and where it starts in `line`, without quotes or a
+/// trailing comment
+pub fn uses(line: &str) -> Option<(usize, &str)> {
+    let key = line.trim_start().trim_start_matches('-').trim_start();
+    let rest = key.strip_prefix("uses:")?;
+    let value = rest.trim_start().trim_start_matches(['"', '\'']);
+    let start = line.len() - value.len();
+    let value = value
+        .split(" #")
+        .next()
+        .unwrap_or_default()
+        .trim_end()
+        .trim_end_matches(['"', '\'']);
+    Some((start, value))
+}